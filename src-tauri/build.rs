@@ -1,3 +1,5 @@
 fn main() {
+    // generate TypeScript definitions of IPC types, see `ds_log_core::ipc`
+    ds_log_core::ipc::export_bindings("../src/bindings").unwrap();
     tauri_build::build()
 }
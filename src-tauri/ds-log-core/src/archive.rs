@@ -0,0 +1,383 @@
+// Open logs inside user provided archives, eg: client_log.txt.gz, Cluster_1.zip, logs.tar.gz
+// An entry is addressed by a chain of names, the last one is the log file and the others
+// are nested archives, eg: ["Cluster_1.tar.gz", "Cluster_1/Master/server_log.txt"]
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use flate2::read::MultiGzDecoder;
+use regex::Regex;
+use once_cell::sync::Lazy;
+use zip::ZipArchive;
+
+use crate::error::LogError;
+use crate::path::{LogPath, PathExt};
+use crate::reader::{LogReader, ZipIndex};
+use crate::source::{calc_v5, strip_home, LogSource, SourceMeta};
+
+/// Max depth of nested archives.
+const MAX_NESTED_DEPTH: usize = 4;
+/// Max size of a decompressed entry, protect against zip bombs.
+const MAX_ENTRY_SIZE: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    /// single compressed file, eg: client_log.txt.gz
+    Gz,
+}
+
+impl ArchiveKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".gz") {
+            Some(ArchiveKind::Gz)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    ArchiveKind::from_name(&path.to_string_lossy()).is_some()
+}
+
+/// client_log.txt, server_log.txt and their backups, same as the discovery code
+pub fn is_log_name(name: &str) -> bool {
+    static LOG_NAME_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(client|server)_log(_\d{4}-\d{2}-\d{2}-\d{2}-\d{2}-\d{2})?\.txt$").unwrap()
+    });
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    LOG_NAME_RE.is_match(file_name)
+}
+
+/// any text file, used if no entry has a log name, eg: DS log.txt, renamed `client_log (1).txt`
+fn is_text_name(name: &str) -> bool {
+    name.to_lowercase().ends_with(".txt")
+}
+
+/// name of the decompressed file, eg: client_log.txt.gz -> client_log.txt
+fn gz_inner_name(name: &str) -> String {
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    file_name[..file_name.len() - 3].to_string()
+}
+
+/// archive content, the outermost one is read from file, nested ones are in memory
+enum Source<'a> {
+    File(&'a Path),
+    Memory(Vec<u8>),
+}
+
+impl Source<'_> {
+    fn reader(&self) -> Result<Box<dyn Read + '_>, LogError> {
+        match self {
+            Source::File(path) => Ok(Box::new(File::open(path).map_err(|e| LogError::io(path, e))?)),
+            Source::Memory(buf) => Ok(Box::new(buf.as_slice())),
+        }
+    }
+}
+
+fn read_limited(r: impl Read, path: &Path) -> Result<Vec<u8>, LogError> {
+    let mut buf = vec![];
+    r.take(MAX_ENTRY_SIZE + 1).read_to_end(&mut buf).map_err(|e| LogError::io(path, e))?;
+    if buf.len() as u64 > MAX_ENTRY_SIZE {
+        return Err(LogError::Zip(format!("{}: entry is too large", path.display())));
+    }
+    Ok(buf)
+}
+
+/// names of all files in the archive
+fn entry_names(kind: ArchiveKind, source: &Source, name: &str, path: &Path) -> Result<Vec<String>, LogError> {
+    match kind {
+        ArchiveKind::Zip => {
+            let names = match source {
+                Source::File(p) => {
+                    let index = ZipIndex::open(p)?;
+                    (0..index.len()).filter_map(|i| index.name_for_index(i)).collect::<Vec<_>>()
+                },
+                Source::Memory(buf) => {
+                    let archive = ZipArchive::new(Cursor::new(buf.as_slice()))
+                        .map_err(|e| LogError::zip(path, None, e))?;
+                    archive.file_names().map(|s| s.to_string()).collect()
+                },
+            };
+            Ok(names.into_iter().filter(|n| !n.ends_with('/')).collect())
+        },
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let reader = source.reader()?;
+            let reader: Box<dyn Read> = match kind {
+                ArchiveKind::TarGz => Box::new(MultiGzDecoder::new(reader)),
+                _ => reader,
+            };
+            let mut archive = tar::Archive::new(reader);
+            let mut names = vec![];
+            for entry in archive.entries().map_err(|e| LogError::io(path, e))? {
+                let entry = entry.map_err(|e| LogError::io(path, e))?;
+                if entry.header().entry_type().is_file() {
+                    if let Ok(p) = entry.path() {
+                        names.push(p.to_string_lossy().to_string());
+                    }
+                }
+            }
+            Ok(names)
+        },
+        ArchiveKind::Gz => Ok(vec![gz_inner_name(name)]),
+    }
+}
+
+/// decompress an entry into memory
+fn read_entry(kind: ArchiveKind, source: &Source, entry: &str, path: &Path) -> Result<Vec<u8>, LogError> {
+    let missing = || LogError::MissingEntry(format!("{}:{}", path.display(), entry));
+    match kind {
+        ArchiveKind::Zip => {
+            let mut reader = match source {
+                Source::File(p) => ZipIndex::open(p)?.open_by_name(entry)?,
+                Source::Memory(buf) => {
+                    let mut archive = ZipArchive::new(Cursor::new(buf.as_slice()))
+                        .map_err(|e| LogError::zip(path, None, e))?;
+                    let file = archive.by_name(entry).map_err(|e| LogError::zip(path, Some(entry), e))?;
+                    return read_limited(file, path);
+                },
+            };
+            read_limited(&mut reader, path)
+        },
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let reader = source.reader()?;
+            let reader: Box<dyn Read> = match kind {
+                ArchiveKind::TarGz => Box::new(MultiGzDecoder::new(reader)),
+                _ => reader,
+            };
+            let mut archive = tar::Archive::new(reader);
+            for file in archive.entries().map_err(|e| LogError::io(path, e))? {
+                let file = file.map_err(|e| LogError::io(path, e))?;
+                if file.path().is_ok_and(|p| p.to_string_lossy() == entry) {
+                    return read_limited(file, path);
+                }
+            }
+            Err(missing())
+        },
+        ArchiveKind::Gz => {
+            if entry != gz_inner_name(&path.to_string_lossy()) && !matches!(source, Source::Memory(_)) {
+                return Err(missing());
+            }
+            read_limited(MultiGzDecoder::new(source.reader()?), path)
+        },
+    }
+}
+
+fn collect_logs(path: &Path, kind: ArchiveKind, source: &Source, name: &str, chain: &mut Vec<String>,
+    result: &mut Vec<LogPath>, is_log: fn(&str) -> bool) -> Result<(), LogError> {
+    for entry in entry_names(kind, source, name, path)? {
+        chain.push(entry.clone());
+        if is_log(&entry) {
+            result.push(LogPath::archive(path, chain.clone()));
+        }
+        else if let Some(inner_kind) = ArchiveKind::from_name(&entry) {
+            // nested archive, errors are ignored so that other entries are still listed
+            // the chain holds every archive entered so far
+            if chain.len() <= MAX_NESTED_DEPTH {
+                if let Ok(buf) = read_entry(kind, source, &entry, path) {
+                    collect_logs(path, inner_kind, &Source::Memory(buf), &entry, chain, result, is_log).ok();
+                }
+            }
+        }
+        chain.pop();
+    }
+    Ok(())
+}
+
+/// list all log-like files in the archive, including nested archives,
+/// all text files are listed if there is no file with a log name
+pub fn list_logs(path: &Path) -> Result<Vec<LogPath>, LogError> {
+    let name = path.to_string_lossy().to_string();
+    let kind = ArchiveKind::from_name(&name)
+        .ok_or_else(|| LogError::Parse(format!("not a supported archive: {}", name)))?;
+    let mut result = vec![];
+    collect_logs(path, kind, &Source::File(path), &name, &mut vec![], &mut result, is_log_name)?;
+    if result.is_empty() {
+        collect_logs(path, kind, &Source::File(path), &name, &mut vec![], &mut result, is_text_name)?;
+    }
+    Ok(result)
+}
+
+/// open an entry by name chain, eg: ["Cluster_1.tar.gz", "Cluster_1/Master/server_log.txt"]
+pub fn open(path: &Path, entries: &[String]) -> Result<LogReader, LogError> {
+    let name = path.to_string_lossy().to_string();
+    let mut kind = ArchiveKind::from_name(&name)
+        .ok_or_else(|| LogError::Parse(format!("not a supported archive: {}", name)))?;
+    let (last, nested) = entries.split_last()
+        .ok_or_else(|| LogError::MissingEntry(format!("{}:", name)))?;
+    if nested.is_empty() && kind == ArchiveKind::Zip {
+        // read from file directly, without decompressing the whole entry if possible
+        return ZipIndex::open(path)?.open_by_name(last);
+    }
+    let mut source = Source::File(path);
+    for entry in nested {
+        let buf = read_entry(kind, &source, entry, path)?;
+        kind = ArchiveKind::from_name(entry)
+            .ok_or_else(|| LogError::Parse(format!("not a supported archive: {}", entry)))?;
+        source = Source::Memory(buf);
+    }
+    let buf = read_entry(kind, &source, last, path)?;
+    Ok(LogReader::ForBuffer(Cursor::new(buf)))
+}
+
+/// entry of user provided archive, nested archives come first in the name chain
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub path: PathBuf,
+    pub entries: Vec<String>,
+}
+
+impl ArchiveEntry {
+    pub(crate) fn deserialize(v: &json::JsonValue) -> Result<LogPath, String> {
+        let path = v["path"].as_str().ok_or("path not found")?;
+        let entries = v["entries"].members()
+            .map(|e| e.as_str().map(|s| s.to_string()))
+            .collect::<Option<Vec<_>>>()
+            .filter(|entries| !entries.is_empty())
+            .ok_or("entries not found")?;
+        Ok(LogPath::archive(path, entries))
+    }
+}
+
+impl LogSource for ArchiveEntry {
+    fn kind(&self) -> &'static str {
+        "archive"
+    }
+
+    fn label(&self) -> String {
+        format!("dstarchive-{}-{}", calc_v5(&self.path.to_string_lossy()), calc_v5(&self.entries.join(":")))
+    }
+
+    fn name(&self) -> String {
+        self.entries.last()
+            .map(|name| name.rsplit(['/', '\\']).next().unwrap_or(name).to_string())
+            .unwrap_or_default()
+    }
+
+    fn location(&self) -> String {
+        strip_home(format!("{}:{}", self.path.to_string_lossy(), self.entries.join(":")))
+    }
+
+    fn local_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    fn open(&self) -> Result<LogReader, LogError> {
+        open(&self.path, &self.entries)
+    }
+
+    fn metadata(&self) -> SourceMeta {
+        self.path.source_meta()
+    }
+
+    fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            "path": self.path.to_string_lossy().to_string(),
+            "entries": self.entries.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use flate2::{write::GzEncoder, Compression};
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(vec![]));
+        for (name, data) in entries {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(vec![]);
+        for (name, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn read(mut reader: LogReader) -> String {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        text
+    }
+
+    #[test]
+    fn nested_archives() {
+        let inner_zip = zip(&[("Caves/server_log.txt", b"caves\n"), ("Caves/cluster.ini", b"")]);
+        let outer = gzip(&tar(&[
+            ("Cluster_1/Master/server_log.txt", b"master\n"),
+            ("Cluster_1/caves.zip", &inner_zip),
+            ("logs/client_log.txt.gz", &gzip(b"client\n")),
+            ("readme.txt", b"not a log\n"),
+        ]));
+        let path = std::env::temp_dir().join(format!("ds-log-archive-{}.tar.gz", uuid::Uuid::new_v4()));
+        std::fs::write(&path, &outer).unwrap();
+
+        let logs = list_logs(&path).unwrap();
+        assert_eq!(logs.len(), 3);
+        let chains: [&[&str]; 3] = [
+            &["Cluster_1/Master/server_log.txt"],
+            &["Cluster_1/caves.zip", "Caves/server_log.txt"],
+            &["logs/client_log.txt.gz", "client_log.txt"],
+        ];
+        for (chain, expected) in chains.iter().zip(["master\n", "caves\n", "client\n"]) {
+            let entries = chain.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            assert_eq!(read(open(&path, &entries).unwrap()), expected);
+        }
+        for log in logs {
+            assert!(read(log.open().unwrap()).ends_with('\n'));
+        }
+        assert!(matches!(open(&path, &["nope.txt".to_string()]), Err(LogError::MissingEntry(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn renamed_logs_are_listed_without_log_names() {
+        let dir = std::env::temp_dir().join(format!("ds-log-archive-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("logs.zip");
+        std::fs::write(&path, zip(&[
+            ("client_log (1).txt", b"renamed\n"),
+            ("ds/log.txt.gz", &gzip(b"ds\n")),
+            ("screenshot.png", b""),
+        ])).unwrap();
+        let logs = list_logs(&path).unwrap();
+        assert_eq!(logs.iter().map(|log| log.get_name()).collect::<Vec<_>>(), vec!["client_log (1).txt", "log.txt"]);
+        assert_eq!(read(logs[0].open().unwrap()), "renamed\n");
+        assert_eq!(read(logs[1].open().unwrap()), "ds\n");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn log_names() {
+        assert!(is_log_name("Cluster_1/Master/server_log.txt"));
+        assert!(is_log_name("backup\\client_log_2025-01-12-20-35-47.txt"));
+        assert!(!is_log_name("server_chat_log.txt"));
+        assert_eq!(ArchiveKind::from_name("LOGS.TGZ"), Some(ArchiveKind::TarGz));
+        assert_eq!(gz_inner_name("dir/client_log.txt.gz"), "client_log.txt");
+    }
+}
@@ -0,0 +1,6 @@
+// standalone command line tool, does not depend on Tauri or any GUI library
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    std::process::exit(ds_log_core::cli::run(&args));
+}
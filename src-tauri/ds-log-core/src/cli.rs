@@ -0,0 +1,513 @@
+// headless command line interface, for dedicated servers without GUI
+// usage: ds-log <command> [args] [--json]
+// only the `ds-log` executable runs these commands, the GUI executable is built without a console on Windows
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use regex::RegexBuilder;
+use serde::Serialize;
+
+use crate::archive::{self, ArchiveKind};
+use crate::cluster::{ClusterConfig, ModIssueKind};
+use crate::discovery::{builtin_roots, collect_logs_from, DiscoveryRoot};
+use crate::ipc::LogTree;
+use crate::parser::{parse_log_content, LogComment};
+use crate::path::LogPath;
+use crate::remote;
+use crate::scripts::{error_frames, LuaSources, DEFAULT_CONTEXT};
+use crate::export::{export, ExportFormat};
+use crate::modinfo::load_mod_meta;
+use crate::redact::Redactor;
+use crate::related::find_related_files;
+use crate::summary::{build_summary, SummaryFormat};
+use crate::tree::{build_log_tree, LogTreeNode};
+
+const USAGE: &str = "\
+Usage: {program} <command> [options]
+
+Commands:
+  list [--root <dir>]...              list all logs under Documents/Klei, ~/.klei, Proton prefixes
+                                      and running dedicated servers, --root adds a Klei dir or
+                                      dedicated server config dir, --no-builtin skips built-in dirs,
+                                      --tree groups logs by branch, cluster and shard
+  entries <archive>                   list logs inside .zip, .gz, .tar or .tar.gz archive
+  parse <file|zip:entry>              print parsed log info
+  errors <file|zip:entry>             print Lua errors, --source prints code of each frame from
+                                      scripts.zip of the local game install and installed mods
+  mods <file|zip:entry>               print loaded mods and why mods were not loaded,
+                                      --meta reads modinfo.lua of installed mods,
+                                      --profile prints load order and load time of each mod
+  cluster <file|zip:entry>            print cluster config of a server log, and mods not matching modoverrides.lua
+  related <file|zip:entry>            print save, cluster and shard files related to a log
+  search <file|zip:entry> <pattern>   print matched lines, use -i to ignore case, --regex for regex
+  export <file|zip:entry>             export log info, --format json|mods_csv|errors_csv (default: json)
+
+Options:
+  --json                              print JSON instead of human-readable text
+  --redact                            hide private info (export only)
+  -h, --help                          print this help
+
+A log inside archive is addressed as <archive>:<entry>, nested archives are chained,
+eg: logs.tar.gz:Cluster_1.zip:Master/server_log.txt
+A remote log is addressed by its http:// or https:// url, use - to read log text from stdin.
+";
+
+/// help text with the name of current executable
+fn usage() -> String {
+    let program = std::env::current_exe().ok()
+        .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or("ds-log".to_string());
+    USAGE.replace("{program}", &program)
+}
+
+/// parsed command line, flags can be placed anywhere
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    json: bool,
+    redact: bool,
+    ignore_case: bool,
+    regex: bool,
+    roots: Vec<PathBuf>,
+    no_builtin: bool,
+    tree: bool,
+    meta: bool,
+    profile: bool,
+    source: bool,
+    format: Option<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut result = Args {
+            command: String::new(),
+            positional: vec![],
+            json: false,
+            redact: false,
+            ignore_case: false,
+            regex: false,
+            roots: vec![],
+            no_builtin: false,
+            tree: false,
+            meta: false,
+            profile: false,
+            source: false,
+            format: None,
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--json" => result.json = true,
+                "--redact" => result.redact = true,
+                "-i" | "--ignore-case" => result.ignore_case = true,
+                "--regex" => result.regex = true,
+                "--root" => result.roots.push(iter.next().ok_or("--root requires a value")?.into()),
+                "--no-builtin" => result.no_builtin = true,
+                "--tree" => result.tree = true,
+                "--meta" => result.meta = true,
+                "--profile" => result.profile = true,
+                "--source" => result.source = true,
+                "--format" => result.format = Some(iter.next().ok_or("--format requires a value")?.clone()),
+                s if s.starts_with("--") => return Err(format!("unknown option: {}", s)),
+                _ if result.command.is_empty() => result.command = arg.clone(),
+                _ => result.positional.push(arg.clone()),
+            }
+        }
+        Ok(result)
+    }
+
+    fn log_path(&self) -> Result<LogPath, String> {
+        let arg = self.positional.first().ok_or("missing log file")?;
+        if arg == "-" {
+            // read log text from stdin
+            let mut buf = vec![];
+            std::io::stdin().read_to_end(&mut buf).map_err(|e| format!("failed to read stdin: {}", e))?;
+            return Ok(LogPath::memory(Some("stdin.txt".to_string()), buf));
+        }
+        let path = parse_log_path(arg);
+        if !path.exists() {
+            return Err(format!("file not exists: {}", path.get_menu_path()));
+        }
+        if path.source().kind() == "external" {
+            // a bare archive, open the only log inside
+            let p = path.local_path().unwrap_or(Path::new(""));
+            if archive::is_archive(p) {
+                let mut logs = archive::list_logs(p)?;
+                return match logs.len() {
+                    0 => Err(format!("no log found in archive: {}", p.display())),
+                    1 => Ok(logs.remove(0)),
+                    _ => Err(format!("multiple logs found in archive, choose one of:\n{}",
+                        logs.iter().map(|log| format!("  {}", log.get_menu_path())).collect::<Vec<_>>().join("\n"))),
+                };
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// `Master.zip:server_log.txt` -> zip entry,
+/// `logs.tar.gz:Cluster_1.zip:Master/server_log.txt` -> entry of nested archive,
+/// `http(s)://...` -> remote log, otherwise a plain file
+pub fn parse_log_path(s: &str) -> LogPath {
+    if remote::is_url(s) {
+        return LogPath::url(s);
+    }
+    // split at every `:` which follows an archive name
+    let mut parts = vec![];
+    let mut start = 0;
+    for (n, _) in s.match_indices(':') {
+        if ArchiveKind::from_name(&s[start..n]).is_some() {
+            parts.push(&s[start..n]);
+            start = n + 1;
+        }
+    }
+    if parts.is_empty() {
+        return LogPath::external(s);
+    }
+    let path = parts[0];
+    let mut entries = parts[1..].iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    entries.push(s[start..].to_string());
+    if entries.len() == 1 && ArchiveKind::from_name(parts[0]) == Some(ArchiveKind::Zip) {
+        LogPath::dst_cloud(path, entries.remove(0))
+    }
+    else {
+        LogPath::archive(path, entries)
+    }
+}
+
+fn default_document_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_DOCUMENTS_DIR") {
+        return Some(dir.into());
+    }
+    std::env::home_dir().map(|home| home.join("Documents"))
+}
+
+fn print_json(value: &impl Serialize) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+fn load_comment(path: &LogPath) -> Result<LogComment, String> {
+    Ok(parse_log_content(&*path.read_content()?)?)
+}
+
+/// run CLI and return exit code
+pub fn run(args: &[String]) -> i32 {
+    let args = match Args::parse(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, usage());
+            return 2;
+        }
+    };
+    let result = match args.command.as_str() {
+        "list" => cmd_list(&args),
+        "entries" => cmd_entries(&args),
+        "parse" => cmd_parse(&args),
+        "errors" => cmd_errors(&args),
+        "mods" => cmd_mods(&args),
+        "cluster" => cmd_cluster(&args),
+        "related" => cmd_related(&args),
+        "search" => cmd_search(&args),
+        "export" => cmd_export(&args),
+        _ => {
+            print!("{}", usage());
+            return 0;
+        }
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+fn cmd_list(args: &Args) -> Result<(), String> {
+    let mut roots = if args.no_builtin { vec![] } else { builtin_roots(default_document_dir().as_deref()) };
+    roots.extend(args.roots.iter().map(DiscoveryRoot::user));
+    if roots.is_empty() {
+        return Err("no dir to search, use --root <dir>".to_string());
+    }
+    let (logs, skipped) = collect_logs_from(&roots);
+    for log in skipped.iter() {
+        eprintln!("skipped: {}", log.error);
+    }
+    if args.tree {
+        let nodes = build_log_tree(&logs);
+        if args.json {
+            print_json(&LogTree { nodes, skipped });
+        }
+        else {
+            nodes.iter().for_each(|node| print_tree(node, 0));
+        }
+    }
+    else if args.json {
+        print_json(&logs.iter().map(|log| log.to_info()).collect::<Vec<_>>());
+    }
+    else {
+        for log in logs {
+            let meta = log.metadata();
+            let mtime = chrono::DateTime::from_timestamp(meta.mtime as i64, 0)
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            println!("{}  {:>10}  {}", mtime, meta.size, log.get_menu_path());
+        }
+    }
+    Ok(())
+}
+
+fn print_tree(node: &LogTreeNode, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match node.log.as_ref().or(node.backups.first()) {
+        Some(_) => {
+            let backups = match node.backups.len() {
+                0 => String::new(),
+                n => format!(" (+{} backups)", n),
+            };
+            println!("{}{}{}  {}", indent, node.name, backups, node.path);
+        },
+        None => println!("{}{} [{}]", indent, node.name, node.count()),
+    }
+    node.children.iter().for_each(|child| print_tree(child, depth + 1));
+}
+
+fn cmd_entries(args: &Args) -> Result<(), String> {
+    let arg = args.positional.first().ok_or("missing archive file")?;
+    let path = PathBuf::from(arg);
+    if !path.is_file() {
+        return Err(format!("file not exists: {}", path.display()));
+    }
+    let logs = archive::list_logs(&path)?;
+    if args.json {
+        print_json(&logs.iter().map(|log| log.to_info()).collect::<Vec<_>>());
+    }
+    else {
+        for log in logs {
+            println!("{}", log.get_menu_path());
+        }
+    }
+    Ok(())
+}
+
+fn cmd_parse(args: &Args) -> Result<(), String> {
+    let path = args.log_path()?;
+    let comment = load_comment(&path)?;
+    if args.json {
+        print_json(&comment);
+    }
+    else {
+        print!("{}", build_summary(&path.get_name(), &comment, SummaryFormat::Plain));
+    }
+    Ok(())
+}
+
+fn cmd_errors(args: &Args) -> Result<(), String> {
+    let path = args.log_path()?;
+    let comment = load_comment(&path)?;
+    if args.json {
+        print_json(&comment.errors);
+        return Ok(());
+    }
+    if comment.errors.is_empty() {
+        println!("No Lua error found");
+    }
+    let sources = args.source.then(|| LuaSources::for_log(&comment.game_type(&path), &comment));
+    for error in comment.errors.iter() {
+        println!("line {}: {}", error.line, error.message);
+        if let Some(moddir) = error.suspected_moddir() {
+            println!("  suspected mod: {} ({})", comment.get_mod_name(&moddir).unwrap_or("?"), moddir);
+        }
+        for frame in error.frames() {
+            println!("    {}", frame);
+        }
+        for frame in sources.iter().flat_map(|s| error_frames(error).into_iter().filter_map(|f| s.snippet(&f, DEFAULT_CONTEXT))) {
+            println!("  {} ({})", frame.frame.key(), frame.origin);
+            for (i, line) in frame.lines.iter().enumerate() {
+                let n = frame.start + i;
+                let mark = if n == frame.frame.line { ">" } else { " " };
+                println!("  {} {:>5} | {}", mark, n, line);
+            }
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn cmd_mods(args: &Args) -> Result<(), String> {
+    let path = args.log_path()?;
+    let comment = load_comment(&path)?;
+    if args.profile {
+        if args.json {
+            print_json(&comment.mod_profile);
+            return Ok(());
+        }
+        println!("{:>3} {:<24} {:>6} {:>8} {:>6} {:>8}  name", "#", "moddir", "load", "register", "files", "prefabs");
+        for p in comment.mod_profile.iter() {
+            println!("{:>3} {:<24} {:>5}s {:>7}s {:>6} {:>8}  {}",
+                p.order, p.moddir, p.load_seconds, p.prefab_seconds, p.prefab_files, p.prefabs, p.name);
+        }
+        let total = comment.mod_profile.iter().map(|p| p.load_seconds + p.prefab_seconds).sum::<u32>();
+        println!("total: {}s", total);
+        return Ok(());
+    }
+    if args.meta {
+        let metas = load_mod_meta(comment.mods.values(), &comment.game_type(&path));
+        if args.json {
+            print_json(&metas);
+            return Ok(());
+        }
+        if metas.is_empty() {
+            println!("No installed mod found");
+        }
+        for m in metas {
+            println!("{} {}", m.moddir, m.name.as_deref().unwrap_or("-"));
+            println!("  author: {}", m.author.as_deref().unwrap_or("-"));
+            println!("  version: {}, api: {}", m.version.as_deref().unwrap_or("-"),
+                m.api_version.map(|n| n.to_string()).unwrap_or("-".into()));
+            let flags = [
+                ("client_only_mod", m.client_only_mod),
+                ("all_clients_require_mod", m.all_clients_require_mod),
+                ("server_only_mod", m.server_only_mod),
+                ("dst_compatible", m.dst_compatible),
+            ];
+            let flags = flags.iter().filter(|(_, v)| *v == Some(true)).map(|(k, _)| *k).collect::<Vec<_>>();
+            if !flags.is_empty() {
+                println!("  flags: {}", flags.join(", "));
+            }
+            if !m.dependencies.is_empty() {
+                println!("  dependencies: {}", m.dependencies.join(", "));
+            }
+            println!("  options: {}", m.options.len());
+            println!("  path: {}", m.path);
+        }
+        return Ok(());
+    }
+    if args.json {
+        print_json(&comment.mods.values().collect::<Vec<_>>());
+        return Ok(());
+    }
+    for m in comment.mods.values() {
+        println!("{:<24} {:<12} {}", m.moddir, m.version.as_deref().unwrap_or("-"), m.name);
+    }
+    for d in comment.mod_diagnostics.iter() {
+        println!();
+        println!("{} {}{}", d.moddir, d.name.as_deref().unwrap_or("-"), if d.loaded { "" } else { " (not loaded)" });
+        for p in d.problems.iter() {
+            match p.line {
+                0 => println!("  {:?}", p.kind),
+                n => println!("  {:?} at line {}: {}", p.kind, n, p.message),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_cluster(args: &Args) -> Result<(), String> {
+    let path = args.log_path()?;
+    let mut config = ClusterConfig::load(&path)
+        .ok_or_else(|| format!("no cluster config found for: {}", path.get_menu_path()))?;
+    config.check_mods(&load_comment(&path)?);
+    if args.json {
+        print_json(&config);
+        return Ok(());
+    }
+    println!("cluster: {}", config.cluster_dir);
+    if let Some(c) = config.cluster.as_ref() {
+        println!("  name: {}", c.cluster_name.as_deref().unwrap_or("-"));
+        println!("  game mode: {}", c.game_mode.as_deref().unwrap_or("-"));
+        println!("  max players: {}", c.max_players.map(|n| n.to_string()).unwrap_or("-".into()));
+        println!("  pvp: {}", c.pvp.map(|b| b.to_string()).unwrap_or("-".into()));
+    }
+    println!("shard: {}", config.shard_path);
+    if let Some(s) = config.shard.as_ref() {
+        println!("  name: {}", s.name.as_deref().unwrap_or("-"));
+        println!("  master: {}", s.is_master.map(|b| b.to_string()).unwrap_or("-".into()));
+    }
+    if let Some(level) = config.level.as_ref() {
+        println!("  world: {} ({})", level.name.as_deref().unwrap_or("-"), level.location.as_deref().unwrap_or("-"));
+        let changed = level.overrides.iter().filter(|o| o.value != "default").collect::<Vec<_>>();
+        for o in changed {
+            println!("    {} = {}", o.name, o.value);
+        }
+    }
+    if let Some(mods) = config.mods.as_ref() {
+        println!("modoverrides.lua: {} enabled, {} disabled",
+            mods.iter().filter(|m| m.enabled).count(), mods.iter().filter(|m| !m.enabled).count());
+    }
+    for e in config.errors.iter() {
+        println!("error: {}", e);
+    }
+    for issue in config.mod_issues.iter() {
+        let name = issue.name.as_deref().map(|n| format!(" ({})", n)).unwrap_or_default();
+        match issue.kind {
+            ModIssueKind::NotLoaded => println!("warning: {} is enabled but not loaded", issue.moddir),
+            ModIssueKind::NotConfigured => println!("warning: {}{} is loaded but not enabled in modoverrides.lua", issue.moddir, name),
+        }
+    }
+    Ok(())
+}
+
+fn cmd_related(args: &Args) -> Result<(), String> {
+    let path = args.log_path()?;
+    let comment = load_comment(&path)?;
+    let mut roots = builtin_roots(default_document_dir().as_deref());
+    roots.extend(args.roots.iter().map(DiscoveryRoot::user));
+    let files = find_related_files(&path, &comment, &roots);
+    if args.json {
+        print_json(&files);
+        return Ok(());
+    }
+    if files.is_empty() {
+        println!("No related file found");
+    }
+    for f in files {
+        println!("{:<14} {:<16} {}", format!("{:?}", f.kind), f.name, f.path);
+    }
+    Ok(())
+}
+
+fn cmd_search(args: &Args) -> Result<(), String> {
+    let path = args.log_path()?;
+    let pattern = args.positional.get(1).ok_or("missing search pattern")?;
+    let pattern = if args.regex { pattern.clone() } else { regex::escape(pattern) };
+    let re = RegexBuilder::new(&pattern)
+        .case_insensitive(args.ignore_case)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let content = path.read_content()?;
+    let mut result = vec![];
+    for (i, line) in content.split(|b| *b == b'\n').enumerate() {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if re.is_match(line) {
+            if args.json {
+                result.push(json::object! { "line": i + 1, "content": line });
+            }
+            else {
+                println!("{}: {}", i + 1, line);
+            }
+        }
+    }
+    if args.json {
+        println!("{}", json::JsonValue::from(result).pretty(2));
+    }
+    Ok(())
+}
+
+fn cmd_export(args: &Args) -> Result<(), String> {
+    let path = args.log_path()?;
+    let format = ExportFormat::parse(args.format.as_deref().unwrap_or("json"))?;
+    let content = path.read_content()?;
+    let comment = parse_log_content(&*content)?;
+    let comment = if args.redact {
+        let mut redactor = Redactor::new();
+        redactor.scan(&content.to_string_lossy());
+        redactor.redact_comment(&comment)
+    }
+    else {
+        comment
+    };
+    print!("{}", export(&path.get_name(), &comment, format));
+    Ok(())
+}
@@ -0,0 +1,326 @@
+// Cluster folders of DST, eg: `Cluster_1/cluster.ini` or `CloudSaves/00CFB14F0C009004/cluster.ini`
+// Only non-secret settings are exposed, `cluster_password` and `cluster_key` are never read.
+// A shard is a dir (`Cluster_1/Master`) or a zip of cloud save (`Master.zip`), both contain
+// `server.ini`, `modoverrides.lua` and `leveldataoverride.lua`.
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::error::LogError;
+use crate::lua::{parse_lua_table, LuaValue};
+use crate::parser::LogComment;
+use crate::path::{LogPath, PathExt};
+use crate::reader::ZipIndex;
+
+/// minimal ini parser for Klei config files, section and key names are case-insensitive
+#[derive(Debug, Clone, Default)]
+pub struct Ini {
+    /// (section, key, value), names are lowercased
+    items: Vec<(String, String, String)>,
+}
+
+impl Ini {
+    pub fn parse(content: &str) -> Self {
+        let mut items = vec![];
+        let mut section = String::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len()-1].trim().to_lowercase();
+            }
+            else if let Some((key, value)) = line.split_once('=') {
+                items.push((section.clone(), key.trim().to_lowercase(), value.trim().to_string()));
+            }
+        }
+        Ini { items }
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        fs::read(path).ok().map(|data| Self::parse(&String::from_utf8_lossy(&data)))
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        let section = section.to_lowercase();
+        let key = key.to_lowercase();
+        self.items.iter().rev()
+            .find(|(s, k, _)| *s == section && *k == key)
+            .map(|(_, _, v)| v.as_str())
+    }
+
+    pub fn get_bool(&self, section: &str, key: &str) -> Option<bool> {
+        match self.get(section, key)?.to_lowercase().as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn get_u32(&self, section: &str, key: &str) -> Option<u32> {
+        self.get(section, key)?.parse().ok()
+    }
+}
+
+/// settings from `cluster.ini`
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct ClusterSettings {
+    pub cluster_name: Option<String>,
+    pub cluster_description: Option<String>,
+    /// survival | endless | wilderness | lavaarena | quagmire
+    pub game_mode: Option<String>,
+    pub max_players: Option<u32>,
+    pub pvp: Option<bool>,
+    /// cooperative | social | competitive | madness
+    pub cluster_intention: Option<String>,
+    pub offline_cluster: Option<bool>,
+    /// caves or other shards are enabled
+    pub shard_enabled: Option<bool>,
+}
+
+impl ClusterSettings {
+    pub fn from_ini(ini: &Ini) -> Self {
+        let text = |section, key| ini.get(section, key).filter(|s| !s.is_empty()).map(str::to_string);
+        Self {
+            cluster_name: text("NETWORK", "cluster_name"),
+            cluster_description: text("NETWORK", "cluster_description"),
+            game_mode: text("GAMEPLAY", "game_mode"),
+            max_players: ini.get_u32("GAMEPLAY", "max_players"),
+            pvp: ini.get_bool("GAMEPLAY", "pvp"),
+            cluster_intention: text("NETWORK", "cluster_intention"),
+            offline_cluster: ini.get_bool("NETWORK", "offline_cluster"),
+            shard_enabled: ini.get_bool("SHARD", "shard_enabled"),
+        }
+    }
+
+    /// read `<dir>/cluster.ini`, None if not exists
+    pub fn load(dir: &Path) -> Option<Self> {
+        Ini::load(&dir.join("cluster.ini")).map(|ini| Self::from_ini(&ini))
+    }
+}
+
+/// settings from `server.ini` of a shard
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct ShardSettings {
+    /// eg: Caves
+    pub name: Option<String>,
+    pub is_master: Option<bool>,
+    pub id: Option<String>,
+    pub server_port: Option<u32>,
+}
+
+impl ShardSettings {
+    pub fn from_ini(ini: &Ini) -> Self {
+        Self {
+            name: ini.get("SHARD", "name").map(str::to_string),
+            is_master: ini.get_bool("SHARD", "is_master"),
+            id: ini.get("SHARD", "id").map(str::to_string),
+            server_port: ini.get_u32("NETWORK", "server_port"),
+        }
+    }
+}
+
+/// a `key = value` pair, value is shown as text
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ConfigOption {
+    pub name: String,
+    pub value: String,
+}
+
+fn to_options(table: Option<&LuaValue>) -> Vec<ConfigOption> {
+    table.map(|t| t.fields()).unwrap_or_default().iter()
+        .map(|(k, v)| ConfigOption { name: k.to_display(), value: v.to_display() })
+        .collect()
+}
+
+/// a mod in `modoverrides.lua`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ModOverride {
+    /// eg: workshop-727774324
+    pub moddir: String,
+    pub enabled: bool,
+    pub options: Vec<ConfigOption>,
+}
+
+/// world settings from `leveldataoverride.lua`
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct LevelSettings {
+    /// eg: SURVIVAL_TOGETHER
+    pub id: Option<String>,
+    pub name: Option<String>,
+    /// forest | cave
+    pub location: Option<String>,
+    pub overrides: Vec<ConfigOption>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ModIssueKind {
+    /// enabled in modoverrides.lua, but not found in log
+    NotLoaded,
+    /// loaded in log, but not enabled in modoverrides.lua, eg: `ForceEnableMod` or a leftover
+    NotConfigured,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ModIssue {
+    pub moddir: String,
+    /// mod name from log, if loaded
+    pub name: Option<String>,
+    pub kind: ModIssueKind,
+}
+
+/// config files of the cluster and shard which a server log belongs to
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct ClusterConfig {
+    pub cluster_dir: String,
+    /// shard dir or zip
+    pub shard_path: String,
+    pub cluster: Option<ClusterSettings>,
+    pub shard: Option<ShardSettings>,
+    /// None if modoverrides.lua not exists
+    pub mods: Option<Vec<ModOverride>>,
+    pub level: Option<LevelSettings>,
+    /// files which exist but can not be parsed, eg: `modoverrides.lua: line 3: expected `}``
+    pub errors: Vec<String>,
+    /// filled by `check_mods`
+    pub mod_issues: Vec<ModIssue>,
+}
+
+/// shard dir, or zip of cloud save
+enum ShardFiles {
+    Dir(PathBuf),
+    Zip(PathBuf),
+}
+
+impl ShardFiles {
+    /// content of a file in shard, None if not exists
+    fn read(&self, name: &str) -> Option<String> {
+        let data = match self {
+            ShardFiles::Dir(dir) => fs::read(dir.join(name)).ok()?,
+            ShardFiles::Zip(path) => {
+                let mut data = vec![];
+                ZipIndex::open(path).ok()?.open_by_name(name).ok()?.read_to_end(&mut data).ok()?;
+                data
+            },
+        };
+        Some(String::from_utf8_lossy(&data).to_string())
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            ShardFiles::Dir(p) | ShardFiles::Zip(p) => p,
+        }
+    }
+}
+
+/// shard of a server log, eg: `Cluster_1/Master/backup/server_log/server_log_<date>.txt` -> `Cluster_1/Master`
+fn find_shard(log: &LogPath) -> Option<ShardFiles> {
+    let path = log.local_path()?;
+    match log.source().kind() {
+        "dstcloud" => Some(ShardFiles::Zip(path.to_path_buf())),
+        "dst" | "external" => {
+            let name = path.file_name_utf8();
+            let dir = path.parent()?;
+            if name == "server_log.txt" {
+                Some(ShardFiles::Dir(dir.to_path_buf()))
+            }
+            else if name.starts_with("server_log_") && dir.ends_with("backup/server_log") {
+                Some(ShardFiles::Dir(dir.parent()?.parent()?.to_path_buf()))
+            }
+            else {
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
+/// shard dir or zip of a server log, None if it is not in a cluster folder
+pub(crate) fn shard_path(log: &LogPath) -> Option<PathBuf> {
+    find_shard(log).map(|shard| shard.path().to_path_buf())
+}
+
+impl ClusterConfig {
+    /// read config files next to a server log, None if it is not in a cluster folder
+    pub fn load(log: &LogPath) -> Option<Self> {
+        let shard = find_shard(log)?;
+        let cluster_dir = shard.path().parent()?;
+        let cluster_ini = Ini::load(&cluster_dir.join("cluster.ini"));
+        let server_ini = shard.read("server.ini").map(|s| Ini::parse(&s));
+        let modoverrides = shard.read("modoverrides.lua");
+        let leveldata = shard.read("leveldataoverride.lua");
+        if cluster_ini.is_none() && server_ini.is_none() && modoverrides.is_none() && leveldata.is_none() {
+            return None;
+        }
+        let mut config = ClusterConfig {
+            cluster_dir: cluster_dir.to_string_lossy().to_string(),
+            shard_path: shard.path().to_string_lossy().to_string(),
+            cluster: cluster_ini.map(|ini| ClusterSettings::from_ini(&ini)),
+            shard: server_ini.map(|ini| ShardSettings::from_ini(&ini)),
+            ..Default::default()
+        };
+        if let Some(content) = modoverrides {
+            match parse_mod_overrides(&content) {
+                Ok(mods) => config.mods = Some(mods),
+                Err(e) => config.errors.push(format!("modoverrides.lua: {}", e.message())),
+            }
+        }
+        if let Some(content) = leveldata {
+            match parse_level_data(&content) {
+                Ok(level) => config.level = Some(level),
+                Err(e) => config.errors.push(format!("leveldataoverride.lua: {}", e.message())),
+            }
+        }
+        Some(config)
+    }
+
+    /// compare mods in modoverrides.lua with mods loaded in log
+    pub fn check_mods(&mut self, comment: &LogComment) {
+        self.mod_issues.clear();
+        // without modoverrides.lua, or the log is too short to load mods
+        let Some(mods) = self.mods.as_ref() else { return; };
+        if comment.sessions.is_empty() {
+            return;
+        }
+        for m in mods.iter().filter(|m| m.enabled) {
+            if !comment.mods.contains_key(&m.moddir) {
+                self.mod_issues.push(ModIssue { moddir: m.moddir.clone(), name: None, kind: ModIssueKind::NotLoaded });
+            }
+        }
+        for m in comment.mods.values() {
+            if !mods.iter().any(|o| o.enabled && o.moddir == m.moddir) {
+                self.mod_issues.push(ModIssue {
+                    moddir: m.moddir.clone(),
+                    name: Some(m.name.clone()),
+                    kind: ModIssueKind::NotConfigured,
+                });
+            }
+        }
+    }
+}
+
+pub fn parse_mod_overrides(content: &str) -> Result<Vec<ModOverride>, LogError> {
+    let table = parse_lua_table(content)?;
+    Ok(table.fields().iter()
+        .filter_map(|(k, v)| Some(ModOverride {
+            moddir: k.as_str()?.to_string(),
+            enabled: v.get("enabled").and_then(LuaValue::as_bool).unwrap_or(false),
+            options: to_options(v.get("configuration_options")),
+        }))
+        .collect())
+}
+
+pub fn parse_level_data(content: &str) -> Result<LevelSettings, LogError> {
+    let table = parse_lua_table(content)?;
+    let text = |key| table.get(key).and_then(LuaValue::as_str).map(str::to_string);
+    Ok(LevelSettings {
+        id: text("id"),
+        name: text("name"),
+        location: text("location"),
+        overrides: to_options(table.get("overrides")),
+    })
+}
@@ -0,0 +1,402 @@
+// Iterate DS/DST log files under discovery roots
+// A root is a Klei dir (`Documents/Klei`, `~/.klei`, ...) which contains `DoNotStarve*` dirs,
+// or a config dir of dedicated server (`<persistent_storage_root>/<conf_dir>`) which contains clusters.
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::io::Read;
+use std::fs;
+use regex::Regex;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::error::LogError;
+use crate::path::{LogPath, PathExt};
+use crate::reader::ZipIndex;
+use crate::steam::{self, DS_APP_ID, DST_APP_ID};
+
+/// a log file (or zip archive) which is found but not readable
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SkippedLog {
+    pub path: String,
+    pub error: LogError,
+}
+
+impl SkippedLog {
+    fn new(path: &Path, error: LogError) -> Self {
+        Self { path: path.to_string_lossy().to_string(), error }
+    }
+}
+
+/// where a root is found
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum RootKind {
+    /// `<document_dir>/Klei`
+    Documents,
+    /// `~/.klei` on Linux
+    Home,
+    /// `Documents` redirected to OneDrive on Windows
+    OneDrive,
+    /// Proton prefix of a Steam library, `steamapps/compatdata/<appid>/pfx/.../Documents/Klei`
+    Proton,
+    /// config dir of dedicated server, from `-persistent_storage_root` and `-conf_dir` of a running server,
+    /// or found under default storage roots and steamcmd installs
+    Dedicated,
+    /// added by user
+    User,
+}
+
+/// a dir to search logs in
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct DiscoveryRoot {
+    pub kind: RootKind,
+    #[ts(type = "string")]
+    pub path: PathBuf,
+}
+
+impl DiscoveryRoot {
+    pub fn new(kind: RootKind, path: impl Into<PathBuf>) -> Self {
+        Self { kind, path: path.into() }
+    }
+
+    /// a dir chosen by user, `Documents` is accepted as well as `Documents/Klei`
+    pub fn user(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let klei = path.join("Klei");
+        Self::new(RootKind::User, if klei.is_dir() { klei } else { path })
+    }
+
+    /// true if the root contains `DoNotStarve*` dirs, otherwise it is a config dir of dedicated server
+    pub(crate) fn is_klei_dir(&self) -> bool {
+        fs::read_dir(&self.path).map(|read| read.flatten()
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("DoNotStarve")))
+            .unwrap_or(false)
+    }
+}
+
+/// probe all built-in roots which exist, `document_dir` is resolved by caller
+pub fn builtin_roots(document_dir: Option<&Path>) -> Vec<DiscoveryRoot> {
+    let mut roots = vec![];
+    if let Some(dir) = document_dir {
+        roots.push(DiscoveryRoot::new(RootKind::Documents, dir.join("Klei")));
+    }
+    if let Some(home) = std::env::home_dir() {
+        roots.push(DiscoveryRoot::new(RootKind::Home, home.join(".klei")));
+    }
+    for var in ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"] {
+        if let Some(dir) = std::env::var_os(var) {
+            roots.push(DiscoveryRoot::new(RootKind::OneDrive, PathBuf::from(dir).join("Documents/Klei")));
+        }
+    }
+    for library in steam::steam_library_dirs() {
+        for app_id in [DS_APP_ID, DST_APP_ID] {
+            let path = library.join("steamapps/compatdata").join(app_id)
+                .join("pfx/drive_c/users/steamuser/Documents/Klei");
+            roots.push(DiscoveryRoot::new(RootKind::Proton, path));
+        }
+    }
+    roots.extend(dedicated_server_roots());
+    roots.retain(|root| root.path.is_dir());
+    roots
+}
+
+/// config dirs of dedicated servers, running or not
+fn dedicated_server_roots() -> Vec<DiscoveryRoot> {
+    let mut roots = running_server_roots();
+    let mut storage_roots = vec![];
+    if let Some(home) = std::env::home_dir() {
+        // default `-persistent_storage_root` is `~/.klei` on Linux and `Documents/Klei` on Windows and macOS
+        storage_roots.push(home.join(".klei"));
+        storage_roots.push(home.join("Documents/Klei"));
+    }
+    // servers installed by steamcmd often keep their storage root next to or inside the install
+    storage_roots.extend(steam::steamcmd_dirs());
+    storage_roots.extend(steam::game_install_dirs(DST_APP_ID));
+    for storage_root in storage_roots {
+        roots.extend(server_conf_dirs(&storage_root).into_iter()
+            .map(|dir| DiscoveryRoot::new(RootKind::Dedicated, dir)));
+    }
+    roots
+}
+
+/// dirs under a storage root which contain clusters, eg: `DoNotStarveTogether` (default `-conf_dir`)
+fn server_conf_dirs(storage_root: &Path) -> Vec<PathBuf> {
+    let Ok(read) = fs::read_dir(storage_root) else { return vec![]; };
+    read.flatten()
+        .map(|entry| entry.path())
+        .filter(|dir| fs::read_dir(dir)
+            .map(|read| read.flatten().any(|entry| entry.path().join("cluster.ini").is_file()))
+            .unwrap_or(false))
+        .collect()
+}
+
+/// config dirs of running dedicated servers, from their command line
+#[cfg(target_os = "linux")]
+fn running_server_roots() -> Vec<DiscoveryRoot> {
+    let mut roots = vec![];
+    let Ok(read) = fs::read_dir("/proc") else { return roots; };
+    for entry in read.flatten() {
+        let proc = entry.path();
+        let Ok(cmdline) = fs::read(proc.join("cmdline")) else { continue; };
+        let args = cmdline.split(|b| *b == 0)
+            .map(|s| String::from_utf8_lossy(s).to_string())
+            .collect::<Vec<_>>();
+        if !args.first().is_some_and(|exe| exe.contains("dontstarve_dedicated_server")) {
+            continue;
+        }
+        let get_arg = |name: &str| args.iter().position(|a| a == name)
+            .and_then(|i| args.get(i + 1)).map(PathBuf::from);
+        let storage_root = match get_arg("-persistent_storage_root") {
+            // relative to working dir of the server
+            Some(dir) => fs::read_link(proc.join("cwd")).map(|cwd| cwd.join(&dir).components().collect()).unwrap_or(dir),
+            None => match std::env::home_dir() {
+                Some(home) => home.join(".klei"),
+                None => continue,
+            },
+        };
+        let conf_dir = get_arg("-conf_dir").unwrap_or("DoNotStarveTogether".into());
+        roots.push(DiscoveryRoot::new(RootKind::Dedicated, storage_root.join(conf_dir)));
+    }
+    roots
+}
+
+#[cfg(not(target_os = "linux"))]
+fn running_server_roots() -> Vec<DiscoveryRoot> {
+    vec![]
+}
+
+fn iter_ds_logs(dir: &Path, skipped: &mut Vec<SkippedLog>) -> Vec<LogPath> {
+    let ds = dir.join("DoNotStarve");
+    let mut result = vec![];
+    for name in ["log.txt", "backup_log.txt"] {
+        let path = ds.join(name);
+        if path.is_file() {
+            match fs::OpenOptions::new().read(true).open(&path) {
+                Ok(_) => result.push(LogPath::ds(path)),
+                Err(e) => skipped.push(SkippedLog::new(&path, LogError::io(&path, e))),
+            }
+        }
+    }
+    result
+}
+
+/// `dst` is `Klei/DoNotStarveTogether*`, or a config dir of dedicated server
+fn iter_dst_logs(dst: &Path, skipped: &mut Vec<SkippedLog>) -> Vec<LogPath> {
+    let mut result = vec![];
+    if !dst.is_dir() {
+        return result;
+    }
+    if dst.join("client_log.txt").is_file() {
+        result.push(LogPath::dst_local(dst.join("client_log.txt")));
+    }
+    if let Ok(read) = fs::read_dir(dst.join("backup/client_log")) {
+        for entry in read.flatten() {
+            let path = entry.path();
+            // name like client_log_2025-01-12-20-35-47.txt
+            static NAME_RE: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r"^client_log_(\d{4}-\d{2}-\d{2}-\d{2}-\d{2}-\d{2})\.txt$").unwrap()
+            });
+            let name = path.file_name_utf8();
+            if path.is_file() && NAME_RE.is_match(name.as_str()) {
+                result.push(LogPath::dst_local(path));
+            }
+            
+        }
+    }
+
+    let read = match fs::read_dir(dst) {
+        Ok(read) => read,
+        Err(e) => {
+            skipped.push(SkippedLog::new(dst, LogError::io(dst, e)));
+            return result;
+        }
+    };
+    for entry in read.flatten() {
+        static UID_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^\d+$").unwrap()
+        });
+        let path = entry.path();
+        let name = path.file_name_utf8();
+        if path.is_dir() && UID_RE.is_match(name.as_str()) {
+            result.extend(iter_dst_cluster_logs(&path, skipped));
+        }
+        else if path.join("cluster.ini").is_file() {
+            // cluster of dedicated server, shards can have any name
+            if let Ok(read) = fs::read_dir(&path) {
+                for entry in read.flatten() {
+                    if entry.path().is_dir() {
+                        result.extend(iter_local_cluster_logs(&entry.path()));
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+fn iter_dst_cluster_logs(dir: &Path, skipped: &mut Vec<SkippedLog>) -> Vec<LogPath> {
+    let mut result = vec![];
+    // local saves
+    if let Ok(read) = fs::read_dir(dir) {
+        for entry in read.flatten() {
+            let path = entry.path();
+            let name = path.file_name_utf8();
+            if path.is_dir() && name.starts_with("Cluster_") {
+                for shard in ["Master", "Caves"] {
+                    let path = path.join(shard);
+                    if path.is_dir() {
+                        result.extend(iter_local_cluster_logs(&path));
+                    }
+                }
+            }
+            
+        }
+    }
+    // cloud saves
+    if let Ok(read) = fs::read_dir(dir.join("CloudSaves")) {
+        for entry in read.flatten() {
+            let path = entry.path();
+            let name = path.file_name_utf8();
+            // 00CFB14F0C009004
+            static CLOUD_HASH: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r"^[0-9A-F]{16}$").unwrap()
+            });
+            if path.is_dir() && CLOUD_HASH.is_match(name.as_str()) {
+                for shard in ["Master", "Caves"] {
+                    let path = path.join(format!("{}.zip", shard));
+                    if path.is_file() {
+                        match iter_cloud_cluster_logs(&path, skipped) {
+                            Ok(logs) => result.extend(logs),
+                            Err(e) => skipped.push(SkippedLog::new(&path, e)),
+                        }
+                    }
+                }
+            }
+            
+        }
+    }
+    result
+}
+
+fn iter_local_cluster_logs(dir: &Path) -> Vec<LogPath> {
+    let mut result = vec![];
+    if dir.join("server_log.txt").is_file() {
+        result.push(LogPath::dst_local(dir.join("server_log.txt")));
+    }
+    if let Ok(read) = fs::read_dir(dir.join("backup/server_log")) {
+        for entry in read.flatten() {
+            let path = entry.path();
+            // name like server_log_2023-04-01-21-43-15.txt
+            static NAME_RE: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r"^server_log_(\d{4}-\d{2}-\d{2}-\d{2}-\d{2}-\d{2})\.txt$").unwrap()
+            });
+            let name = path.file_name_utf8();
+            if path.is_file() && NAME_RE.is_match(name.as_str()) {
+                result.push(LogPath::dst_local(path));
+            }
+            
+        }
+    }
+    result
+}
+
+/// returns error if the archive is broken, unreadable entries are skipped
+fn iter_cloud_cluster_logs(dir: &Path, skipped: &mut Vec<SkippedLog>) -> Result<Vec<LogPath>, LogError> {
+    let mut result = vec![];
+    // iterate all files in the zip
+    let archive = ZipIndex::open(dir)?;
+    for i in 0..archive.len() {
+        let Some(name) = archive.name_for_index(i) else { continue; };
+        // server_log.txt
+        // backup/server_log/server_log_2023-03-28-21-10-49.txt
+        static NAME_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^backup/server_log/server_log_(\d{4}-\d{2}-\d{2}-\d{2}-\d{2}-\d{2})\.txt$").unwrap()
+        });
+        if name == "server_log.txt" || NAME_RE.is_match(&name) {
+            let readable = archive.open_by_index(i)
+                .and_then(|mut file| Ok(file.read(&mut [0; 1])?));
+            match readable {
+                Ok(_) => result.push(LogPath::dst_cloud(dir.to_path_buf(), name)),
+                Err(error) => skipped.push(SkippedLog {
+                    path: format!("{}:{}", dir.to_string_lossy(), name),
+                    error,
+                }),
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// List all logs for DS/DST under `<document_dir>/Klei` and sort by mtime.
+/// Unreadable files are skipped and returned with the reason.
+pub fn collect_logs(document_dir: &Path) -> (Vec<LogPath>, Vec<SkippedLog>) {
+    collect_logs_from(&[DiscoveryRoot::new(RootKind::Documents, document_dir.join("Klei"))])
+}
+
+/// List all logs under `roots` and sort by mtime, logs found in more than one root are listed once.
+/// A user root which does not exist is reported as skipped.
+pub fn collect_logs_from(roots: &[DiscoveryRoot]) -> (Vec<LogPath>, Vec<SkippedLog>) {
+    let mut result = vec![];
+    let mut skipped = vec![];
+    let mut seen = HashSet::new();
+    for root in roots {
+        if !seen.insert(fs::canonicalize(&root.path).unwrap_or(root.path.clone())) {
+            continue;
+        }
+        if !root.path.is_dir() {
+            if root.kind == RootKind::User {
+                skipped.push(SkippedLog::new(&root.path,
+                    LogError::NotFound(root.path.display().to_string())));
+            }
+            continue;
+        }
+        if root.is_klei_dir() {
+            result.extend(iter_ds_logs(&root.path, &mut skipped));
+            for identifier in ["DoNotStarveTogether", "DoNotStarveTogetherBetaBranch", "DoNotStarveTogetherRail"] {
+                let dst = root.path.join(identifier);
+                if dst.is_dir() {
+                    result.extend(iter_dst_logs(&dst, &mut skipped));
+                }
+            }
+        }
+        else {
+            result.extend(iter_dst_logs(&root.path, &mut skipped));
+        }
+    }
+    // nested roots, eg: `~/.klei` and `~/.klei/DoNotStarveTogether`
+    let mut labels = HashSet::new();
+    result.retain(|log| labels.insert(log.to_label()));
+    // sort
+    result.sort_by(|a, b| {
+        let a = a.metadata().mtime;
+        let b = b.metadata().mtime;
+        b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    (result, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_conf_dirs_contain_clusters() {
+        let root = std::env::temp_dir().join(format!("ds-log-discovery-{}", uuid::Uuid::new_v4()));
+        let master = root.join("DoNotStarveTogether/MyDediServer/Master");
+        fs::create_dir_all(&master).unwrap();
+        fs::write(root.join("DoNotStarveTogether/MyDediServer/cluster.ini"), "[GAMEPLAY]\n").unwrap();
+        fs::write(master.join("server_log.txt"), "[00:00:00]: Starting Up\n").unwrap();
+        fs::create_dir_all(root.join("bin64")).unwrap();
+        fs::create_dir_all(root.join("mods/workshop-123")).unwrap();
+
+        let conf_dirs = server_conf_dirs(&root);
+        assert_eq!(conf_dirs, vec![root.join("DoNotStarveTogether")]);
+        let roots = conf_dirs.into_iter().map(|dir| DiscoveryRoot::new(RootKind::Dedicated, dir)).collect::<Vec<_>>();
+        let (logs, skipped) = collect_logs_from(&roots);
+        assert!(skipped.is_empty());
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].get_name(), "server_log.txt");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+// Error type shared by reader, parser, discovery and tauri commands
+use std::path::Path;
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Serialized as `{ kind: "io", message: "..." }` for the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum LogError {
+    /// failed to read a file or directory
+    Io(String),
+    /// not allowed to read a file or directory
+    Permission(String),
+    /// file or log not exists
+    NotFound(String),
+    /// not a valid zip archive, or the archive uses unsupported features
+    Zip(String),
+    /// entry not found in zip archive, eg: `Master.zip:server_log.txt`
+    MissingEntry(String),
+    /// invalid content or argument
+    Parse(String),
+}
+
+impl LogError {
+    /// io error with the path which caused it
+    pub fn io(path: &Path, e: std::io::Error) -> Self {
+        let message = format!("{}: {}", path.display(), e);
+        match e.kind() {
+            std::io::ErrorKind::PermissionDenied => LogError::Permission(message),
+            std::io::ErrorKind::NotFound => LogError::NotFound(message),
+            _ => LogError::Io(message),
+        }
+    }
+
+    /// zip error with the archive path and entry name
+    pub fn zip(path: &Path, entry: Option<&str>, e: zip::result::ZipError) -> Self {
+        use zip::result::ZipError;
+        match e {
+            ZipError::Io(e) => LogError::io(path, e),
+            ZipError::FileNotFound => LogError::MissingEntry(
+                format!("{}:{}", path.display(), entry.unwrap_or_default())),
+            e => LogError::Zip(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            LogError::Io(s) |
+            LogError::Permission(s) |
+            LogError::NotFound(s) |
+            LogError::Zip(s) |
+            LogError::MissingEntry(s) |
+            LogError::Parse(s) => s.as_str(),
+        }
+    }
+}
+
+impl std::fmt::Display for LogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let prefix = match self {
+            LogError::Io(_) => "io error",
+            LogError::Permission(_) => "permission denied",
+            LogError::NotFound(_) => "not found",
+            LogError::Zip(_) => "zip error",
+            LogError::MissingEntry(_) => "missing zip entry",
+            LogError::Parse(_) => "parse error",
+        };
+        write!(f, "{}: {}", prefix, self.message())
+    }
+}
+
+impl std::error::Error for LogError {}
+
+impl From<std::io::Error> for LogError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::PermissionDenied => LogError::Permission(e.to_string()),
+            std::io::ErrorKind::NotFound => LogError::NotFound(e.to_string()),
+            _ => LogError::Io(e.to_string()),
+        }
+    }
+}
+
+impl From<LogError> for String {
+    fn from(e: LogError) -> Self {
+        e.to_string()
+    }
+}
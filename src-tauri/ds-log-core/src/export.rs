@@ -0,0 +1,247 @@
+// export parsed log metadata for spreadsheets and dashboards
+//
+// JSON schema (version 1), all line numbers are 1-based:
+// {
+//   "schema": "dst-log-reader/log-comment",
+//   "version": 1,
+//   "filename": "client_log.txt",
+//   "kind": "unknown" | "ds" | "dst_client" | "dst_server" | "dedicated" | "worldgen",
+//       dst_server: a shard started by a client, dedicated: a dedicated server
+//   "build": { "version": "654321", "platform": "WIN32_STEAM", "arch": "64-bit" },
+//   "flags": { "has_stacktrace": bool, "has_lua_crash": bool, "has_c_crash": bool },
+//   "ds": { "dlcs": ["Reign of Giants", "Shipwrecked", "Hamlet"], "save_slot": 1 | null,
+//           "world_type": "survival" | "cave" | "adventure" | "shipwrecked" | "volcano" | "porkland" | null,
+//           "save_file": "cave_1_2" | null, "crash": "..." | null } | null,
+//       null for DST logs, save_slot is 1-based, crash is an engine crash without a Lua error screen
+//   "system": { "os": "..." | null, "cpu": "..." | null, "memory_mb": 16291 | null, "available_memory_mb": 8541 | null,
+//               "gpu_vendor": "NVIDIA Corporation" | null, "renderer": "..." | null, "gl_version": "..." | null,
+//               "display": "1920x1080" | null, "steam_branch": "public" | null, "steam_build_id": "..." | null,
+//               "language": "english" | null, "warnings": ["low_memory" | "32_bit" | "software_rendering"] },
+//       launch info before the first game instance, gpu_vendor, renderer and gl_version are GL_VENDOR,
+//       GL_RENDERER and GL_VERSION, the command line is not exported since it may contain passwords and tokens
+//   "total_time_secs": 3723,
+//   "databundles": [ { "name": "scripts.zip", "mounted": true } ],
+//       mounted: true if the zip is used, false if skipped (loose files)
+//   "mods": [ { "moddir": "workshop-727774324", "name": "Craft Pot", "version": "0.15.0" | null,
+//               "workshop_id": "727774324" | null, "registered": bool } ],
+//       registered: moddir is inserted by `ModIndex:GetModsToLoad` (DST only)
+//   "sessions": [ { "start_line": 60, "end_line": 120, "start_time_secs": 1 } ],
+//       a session starts from `cGame::StartPlaying`
+//   "fields": [ { "start_line": 77, "end_line": 86, "type": "lua_crash", "extra": "..." } ],
+//   "errors": [ { "line": 77, "message": "...", "traceback": ["..."], "suspected_moddir": "..." | null } ]
+// }
+// Fields may be added in the same version, renaming or removing a field bumps the version.
+
+use crate::error::LogError;
+use crate::parser::LogComment;
+
+pub const EXPORT_SCHEMA: &str = "dst-log-reader/log-comment";
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    ModsCsv,
+    ErrorsCsv,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Result<Self, LogError> {
+        match s {
+            "json" => Ok(ExportFormat::Json),
+            "mods_csv" => Ok(ExportFormat::ModsCsv),
+            "errors_csv" => Ok(ExportFormat::ErrorsCsv),
+            _ => Err(LogError::Parse(format!("unknown export format: {}", s))),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::ModsCsv | ExportFormat::ErrorsCsv => "csv",
+        }
+    }
+
+    pub fn file_suffix(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "",
+            ExportFormat::ModsCsv => "_mods",
+            ExportFormat::ErrorsCsv => "_errors",
+        }
+    }
+}
+
+fn to_secs(time: &[u32]) -> u32 {
+    match time {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        _ => 0,
+    }
+}
+
+pub fn export_json(name: &str, comment: &LogComment) -> json::JsonValue {
+    let mut databundles = comment.databundles_mounting_state.iter().collect::<Vec<_>>();
+    databundles.sort();
+    json::object! {
+        "schema": EXPORT_SCHEMA,
+        "version": EXPORT_SCHEMA_VERSION,
+        "filename": name,
+        "kind": comment.kind.as_str(),
+        "build": {
+            "version": comment.build_version.clone(),
+            "platform": comment.build_platform.clone(),
+            "arch": comment.build_arch.clone(),
+        },
+        "flags": {
+            "has_stacktrace": comment.has_stacktrace,
+            "has_lua_crash": comment.has_lua_crash,
+            "has_c_crash": comment.has_c_crash,
+        },
+        "ds": comment.ds.as_ref().map(|ds| json::object! {
+            "dlcs": ds.dlcs.iter().map(|d| d.label()).collect::<Vec<_>>(),
+            "save_slot": ds.save_slot,
+            "world_type": ds.world_type.clone(),
+            "save_file": ds.save_file.clone(),
+            "crash": ds.crash.clone(),
+        }),
+        "system": json::object! {
+            "os": comment.system.os.clone(),
+            "cpu": comment.system.cpu.clone(),
+            "memory_mb": comment.system.memory_mb,
+            "available_memory_mb": comment.system.available_memory_mb,
+            "gpu_vendor": comment.system.gpu_vendor.clone(),
+            "renderer": comment.system.renderer.clone(),
+            "gl_version": comment.system.gl_version.clone(),
+            "display": comment.system.display.clone(),
+            "steam_branch": comment.system.steam_branch.clone(),
+            "steam_build_id": comment.system.steam_build_id.clone(),
+            "language": comment.system.language.clone(),
+            // no command_line, see schema
+            "warnings": comment.system.warnings.iter().map(|w| w.as_str()).collect::<Vec<_>>(),
+        },
+        "total_time_secs": to_secs(&comment.total_time),
+        "databundles": databundles.into_iter().map(|(name, mounted)| {
+            json::object! {
+                "name": name.clone(),
+                "mounted": *mounted,
+            }
+        }).collect::<Vec<_>>(),
+        "mods": comment.mods.values().map(|m| {
+            json::object! {
+                "moddir": m.moddir.clone(),
+                "name": m.name.clone(),
+                "version": m.version.clone(),
+                "workshop_id": m.workshop_id.clone(),
+                "registered": comment.mods_registed.contains_key(&m.moddir),
+            }
+        }).collect::<Vec<_>>(),
+        "sessions": comment.sessions.iter().map(|s| {
+            json::object! {
+                "start_line": s.start,
+                "end_line": s.end,
+                "start_time_secs": to_secs(&s.start_time),
+            }
+        }).collect::<Vec<_>>(),
+        "fields": comment.fields.iter().map(|f| {
+            json::object! {
+                "start_line": f.start,
+                "end_line": f.end,
+                "type": f.kind.clone(),
+                "extra": f.extra.clone(),
+            }
+        }).collect::<Vec<_>>(),
+        "errors": comment.errors.iter().map(|e| {
+            json::object! {
+                "line": e.line,
+                "message": e.message.clone(),
+                "traceback": e.traceback.clone(),
+                "suspected_moddir": e.suspected_moddir(),
+            }
+        }).collect::<Vec<_>>(),
+    }
+}
+
+/// quote a csv cell if needed (RFC 4180)
+fn csv_cell(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn csv_row(cells: &[&str]) -> String {
+    let mut row = cells.iter().map(|s| csv_cell(s)).collect::<Vec<_>>().join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// columns: moddir,name,version,workshop_id,registered
+pub fn export_mods_csv(comment: &LogComment) -> String {
+    let mut result = csv_row(&["moddir", "name", "version", "workshop_id", "registered"]);
+    for m in comment.mods.values() {
+        let registered = comment.mods_registed.contains_key(&m.moddir).to_string();
+        result.push_str(&csv_row(&[
+            &m.moddir,
+            &m.name,
+            m.version.as_deref().unwrap_or_default(),
+            m.workshop_id.as_deref().unwrap_or_default(),
+            &registered,
+        ]));
+    }
+    result
+}
+
+/// columns: line,message,suspected_moddir,suspected_mod_name,first_frame,frame_count
+pub fn export_errors_csv(comment: &LogComment) -> String {
+    let mut result = csv_row(&["line", "message", "suspected_moddir", "suspected_mod_name", "first_frame", "frame_count"]);
+    for e in comment.errors.iter() {
+        let moddir = e.suspected_moddir().unwrap_or_default();
+        let frames = e.frames().collect::<Vec<_>>();
+        result.push_str(&csv_row(&[
+            &e.line.to_string(),
+            &e.message,
+            &moddir,
+            comment.get_mod_name(&moddir).unwrap_or_default(),
+            frames.first().cloned().unwrap_or_default(),
+            &frames.len().to_string(),
+        ]));
+    }
+    result
+}
+
+pub fn export(name: &str, comment: &LogComment, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Json => export_json(name, comment).pretty(2),
+        ExportFormat::ModsCsv => export_mods_csv(comment),
+        ExportFormat::ErrorsCsv => export_errors_csv(comment),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_content;
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_cell("plain"), "plain");
+        assert_eq!(csv_cell("a, b"), "\"a, b\"");
+        assert_eq!(csv_cell("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_cell("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_row(&["", "x"]), ",x\r\n");
+    }
+
+    #[test]
+    fn mods_csv_in_load_order() {
+        let comment = parse_log_content("\
+[00:00:01]: ModIndex:GetModsToLoad inserting moddir, \tworkshop-2
+[00:00:02]: Loading mod: workshop-2 (Pots, \"Pans\") Version:1.0
+[00:00:02]: Loading mod: workshop-1 (Plain)
+".as_bytes()).unwrap();
+        assert_eq!(export_mods_csv(&comment), "\
+moddir,name,version,workshop_id,registered\r
+workshop-2,\"Pots, \"\"Pans\"\"\",1.0,2,true\r
+workshop-1,Plain,,1,false\r
+");
+    }
+}
@@ -0,0 +1,473 @@
+// Seekable reader over a deflated zip entry
+// While inflating, a checkpoint (position + last 32KiB of output) is saved at a deflate block boundary
+// about every `SPAN` bytes. Seeking restores the nearest checkpoint and inflates at most `SPAN` bytes,
+// instead of starting over from the beginning of the entry.
+// Checkpoints are cached per entry and reused by later readers, keyed by archive mtime and entry CRC.
+// Output is checked against the entry CRC when the whole entry is inflated from the beginning.
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use flate2::Crc;
+use miniz_oxide::inflate::core::inflate_flags::{TINFL_FLAG_HAS_MORE_INPUT, TINFL_FLAG_STOP_ON_BLOCK_BOUNDARY};
+use miniz_oxide::inflate::core::{decompress, BlockBoundaryState, DecompressorOxide};
+use miniz_oxide::inflate::TINFLStatus;
+use once_cell::sync::Lazy;
+
+use crate::path::PathExt;
+use crate::reader::EntryRange;
+
+/// wrapping output buffer, also the deflate dictionary (32KiB)
+const BUF_SIZE: usize = 32 * 1024;
+/// distance between checkpoints in uncompressed bytes
+const SPAN: u64 = 1024 * 1024;
+const INPUT_SIZE: usize = 64 * 1024;
+/// max number of cached entry indexes
+const MAX_CACHED: usize = 32;
+
+#[derive(Clone)]
+struct Checkpoint {
+    /// compressed bytes consumed
+    in_pos: u64,
+    /// uncompressed offset
+    out_pos: u64,
+    state: BlockBoundaryState,
+    /// output preceding `out_pos`, at most 32KiB
+    window: Arc<[u8]>,
+}
+
+/// checkpoints of an entry, extended by every reader which inflates further
+struct InflateIndex {
+    mtime: f64,
+    crc32: u32,
+    points: Vec<Checkpoint>,
+}
+
+impl InflateIndex {
+    /// latest checkpoint not after `pos`
+    fn find(&self, pos: u64) -> Option<Checkpoint> {
+        let n = self.points.partition_point(|p| p.out_pos <= pos);
+        n.checked_sub(1).map(|i| self.points[i].clone())
+    }
+
+    fn push(&mut self, point: Checkpoint) {
+        let last = self.points.last().map(|p| p.out_pos).unwrap_or(0);
+        if point.out_pos >= last + SPAN {
+            self.points.push(point);
+        }
+    }
+}
+
+type IndexRef = Arc<Mutex<InflateIndex>>;
+
+static INDEX_CACHE: Lazy<Mutex<HashMap<(PathBuf, String), IndexRef>>> = Lazy::new(Default::default);
+
+/// get cached index of the entry, or a new one if the archive is changed
+fn get_index(archive: &Path, name: &str, crc32: u32) -> IndexRef {
+    let mtime = archive.mtime_f64();
+    let mut cache = INDEX_CACHE.lock().unwrap();
+    let key = (archive.to_path_buf(), name.to_string());
+    if let Some(index) = cache.get(&key) {
+        let valid = {
+            let index = index.lock().unwrap();
+            index.mtime == mtime && index.crc32 == crc32
+        };
+        if valid {
+            return index.clone();
+        }
+    }
+    if cache.len() >= MAX_CACHED {
+        // drop indexes not used by any reader
+        cache.retain(|_, index| Arc::strong_count(index) > 1);
+    }
+    let index = Arc::new(Mutex::new(InflateIndex { mtime, crc32, points: vec![] }));
+    cache.insert(key, index.clone());
+    index
+}
+
+pub struct InflateReader {
+    /// compressed data
+    data: EntryRange,
+    /// uncompressed size from zip header
+    size: u64,
+    crc32: u32,
+    /// crc of output since the beginning of the entry, None after restoring a checkpoint
+    crc: Option<Crc>,
+    index: IndexRef,
+    inflater: Box<DecompressorOxide>,
+    buf: Box<[u8]>,
+    /// next write position in `buf`
+    buf_pos: usize,
+    /// unread output in `buf[avail_start..avail_start + avail]`
+    avail_start: usize,
+    avail: usize,
+    input: Box<[u8]>,
+    in_start: usize,
+    in_end: usize,
+    in_eof: bool,
+    /// compressed bytes consumed
+    in_pos: u64,
+    /// uncompressed bytes produced
+    out_pos: u64,
+    done: bool,
+}
+
+impl InflateReader {
+    pub fn new(data: EntryRange, size: u64, archive: &Path, name: &str, crc32: u32) -> Self {
+        Self {
+            data,
+            size,
+            crc32,
+            crc: Some(Crc::new()),
+            index: get_index(archive, name, crc32),
+            inflater: Box::default(),
+            buf: vec![0; BUF_SIZE].into_boxed_slice(),
+            buf_pos: 0,
+            avail_start: 0,
+            avail: 0,
+            input: vec![0; INPUT_SIZE].into_boxed_slice(),
+            in_start: 0,
+            in_end: 0,
+            in_eof: false,
+            in_pos: 0,
+            out_pos: 0,
+            done: false,
+        }
+    }
+
+    /// uncompressed size of the entry
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// current read position
+    fn pos(&self) -> u64 {
+        self.out_pos - self.avail as u64
+    }
+
+    /// output preceding `buf_pos`, oldest first
+    fn window(&self) -> Arc<[u8]> {
+        let len = (self.out_pos as usize).min(BUF_SIZE);
+        let start = (self.buf_pos + BUF_SIZE - len) % BUF_SIZE;
+        let mut window = Vec::with_capacity(len);
+        if start + len <= BUF_SIZE {
+            window.extend_from_slice(&self.buf[start..start + len]);
+        } else {
+            window.extend_from_slice(&self.buf[start..]);
+            window.extend_from_slice(&self.buf[..self.buf_pos]);
+        }
+        window.into()
+    }
+
+    /// restore state from a checkpoint, or the beginning of the entry
+    fn restore(&mut self, point: Option<Checkpoint>) -> io::Result<()> {
+        self.buf_pos = 0;
+        self.avail = 0;
+        self.in_start = 0;
+        self.in_end = 0;
+        self.in_eof = false;
+        self.done = false;
+        match point {
+            Some(point) => {
+                *self.inflater = DecompressorOxide::from_block_boundary_state(&point.state);
+                // place the window before position 0 of the wrapping buffer
+                let len = point.window.len();
+                self.buf[BUF_SIZE - len..].copy_from_slice(&point.window);
+                self.in_pos = point.in_pos;
+                self.out_pos = point.out_pos;
+                // output before the checkpoint is skipped, so the crc can't be checked any more
+                self.crc = None;
+            },
+            None => {
+                *self.inflater = DecompressorOxide::new();
+                self.in_pos = 0;
+                self.out_pos = 0;
+                self.crc = Some(Crc::new());
+            }
+        }
+        self.data.seek(SeekFrom::Start(self.in_pos))?;
+        Ok(())
+    }
+
+    /// inflate next chunk into `buf`, returns number of new bytes, 0 at the end
+    fn inflate(&mut self) -> io::Result<usize> {
+        debug_assert_eq!(self.avail, 0);
+        while !self.done {
+            if self.in_start == self.in_end && !self.in_eof {
+                self.in_start = 0;
+                self.in_end = self.data.read(&mut self.input)?;
+                self.in_eof = self.in_end == 0;
+            }
+            let mut flags = TINFL_FLAG_STOP_ON_BLOCK_BOUNDARY;
+            if !self.in_eof {
+                flags |= TINFL_FLAG_HAS_MORE_INPUT;
+            }
+            let (status, consumed, written) = decompress(&mut self.inflater,
+                &self.input[self.in_start..self.in_end], &mut self.buf, self.buf_pos, flags);
+            self.in_start += consumed;
+            self.in_pos += consumed as u64;
+            let start = self.buf_pos;
+            self.buf_pos = (self.buf_pos + written) % BUF_SIZE;
+            self.out_pos += written as u64;
+            if let Some(crc) = self.crc.as_mut() {
+                crc.update(&self.buf[start..start + written]);
+            }
+            match status {
+                TINFLStatus::Done => {
+                    self.done = true;
+                    if let Some(crc) = self.crc.as_ref().filter(|crc| crc.sum() != self.crc32) {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData,
+                            format!("crc32 mismatch: {:08x}, expected {:08x}", crc.sum(), self.crc32)));
+                    }
+                },
+                TINFLStatus::BlockBoundary => {
+                    if let Some(state) = self.inflater.block_boundary_state() {
+                        let point = Checkpoint {
+                            in_pos: self.in_pos,
+                            out_pos: self.out_pos,
+                            state,
+                            window: self.window(),
+                        };
+                        self.index.lock().unwrap().push(point);
+                    }
+                },
+                TINFLStatus::NeedsMoreInput | TINFLStatus::HasMoreOutput => {},
+                TINFLStatus::FailedCannotMakeProgress => {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "deflate stream is truncated"));
+                },
+                _ => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("failed to inflate: {:?}", status)));
+                },
+            }
+            if written > 0 {
+                self.avail_start = start;
+                self.avail = written;
+                return Ok(written);
+            }
+        }
+        Ok(0)
+    }
+
+    fn seek_to(&mut self, target: u64) -> io::Result<()> {
+        let pos = self.pos();
+        if target >= pos && target <= self.out_pos {
+            // inside unread output
+            let skip = (target - pos) as usize;
+            self.avail_start += skip;
+            self.avail -= skip;
+            return Ok(());
+        }
+        let point = self.index.lock().unwrap().find(target);
+        let point_pos = point.as_ref().map(|p| p.out_pos).unwrap_or(0);
+        if target < pos || point_pos > self.out_pos {
+            self.restore(point)?;
+        }
+        // inflate and drop output before target
+        self.avail = 0;
+        while self.out_pos < target {
+            if self.inflate()? == 0 {
+                // seek beyond the end is allowed, just read nothing
+                return Ok(());
+            }
+            if self.out_pos > target {
+                let skip = self.avail - (self.out_pos - target) as usize;
+                self.avail_start += skip;
+                self.avail -= skip;
+            }
+            else {
+                self.avail = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Read for InflateReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.avail == 0 && self.inflate()? == 0 {
+            return Ok(0);
+        }
+        let n = buf.len().min(self.avail);
+        buf[..n].copy_from_slice(&self.buf[self.avail_start..self.avail_start + n]);
+        self.avail_start += n;
+        self.avail -= n;
+        Ok(n)
+    }
+}
+
+impl Seek for InflateReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => self.size.checked_add_signed(n),
+            SeekFrom::Current(n) => self.pos().checked_add_signed(n),
+        };
+        let target = target.ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidInput, "invalid seek to a negative position"))?;
+        self.seek_to(target)?;
+        Ok(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use crate::reader::{LogReader, ZipIndex};
+
+    /// log-like text with varied lines, so deflate emits many blocks
+    fn sample(size: usize, seed: u64) -> Vec<u8> {
+        let mut data = Vec::with_capacity(size + 100);
+        let mut n = seed;
+        while data.len() < size {
+            n = n.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            writeln!(data, "[{:02}:{:02}:{:02}]: line {} value {:x}", n % 24, n % 60, n % 61, data.len(), n >> 20).unwrap();
+        }
+        data
+    }
+
+    fn write_zip(path: &Path, data: &[u8]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("server_log.txt", options).unwrap();
+        zip.write_all(data).unwrap();
+        zip.finish().unwrap();
+    }
+
+    fn temp_zip() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ds-log-inflate-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("Master.zip")
+    }
+
+    fn open(path: &Path) -> LogReader {
+        let reader = ZipIndex::open(path).unwrap().open_by_name("server_log.txt").unwrap();
+        assert!(matches!(reader, LogReader::ForDeflatedEntry(_)));
+        reader
+    }
+
+    fn read_at(reader: &mut LogReader, pos: u64, len: usize) -> Vec<u8> {
+        assert_eq!(reader.seek(SeekFrom::Start(pos)).unwrap(), pos);
+        let mut buf = vec![0; len];
+        let mut n = 0;
+        while n < len {
+            match reader.read(&mut buf[n..]).unwrap() {
+                0 => break,
+                m => n += m,
+            }
+        }
+        buf.truncate(n);
+        buf
+    }
+
+    #[test]
+    fn seek_matches_full_inflate() {
+        let path = temp_zip();
+        let data = sample(5 * SPAN as usize + 12345, 1);
+        write_zip(&path, &data);
+
+        let mut full = vec![];
+        open(&path).read_to_end(&mut full).unwrap();
+        assert_eq!(full, data);
+        let index = get_index(&path, "server_log.txt", crc32(&path));
+        assert!(index.lock().unwrap().points.len() >= 4);
+
+        let mut reader = open(&path);
+        let check = |reader: &mut LogReader, pos: u64, len: usize| {
+            let end = (pos as usize + len).min(data.len());
+            let start = (pos as usize).min(end);
+            assert_eq!(read_at(reader, pos, len), &data[start..end], "read {} bytes at {}", len, pos);
+        };
+        // forward, backward, across checkpoint boundaries and after the end
+        let mut n = 7u64;
+        for _ in 0..40 {
+            n = n.wrapping_mul(6364136223846793005).wrapping_add(1);
+            check(&mut reader, (n >> 11) % data.len() as u64, 1 + (n % 70000) as usize);
+        }
+        for span in 1..5 {
+            check(&mut reader, span * SPAN - 10, 20);
+            check(&mut reader, span * SPAN - BUF_SIZE as u64 - 1, BUF_SIZE + 2);
+        }
+        check(&mut reader, 0, 100);
+        check(&mut reader, data.len() as u64 - 5, 100);
+        check(&mut reader, data.len() as u64 + 10, 10);
+        assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), data.len() as u64 - 3);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn cache_is_invalidated_by_mtime() {
+        let path = temp_zip();
+        let data = sample(3 * SPAN as usize, 2);
+        write_zip(&path, &data);
+        let crc = crc32(&path);
+        open(&path).read_to_end(&mut vec![]).unwrap();
+        let index = get_index(&path, "server_log.txt", crc);
+        assert!(!index.lock().unwrap().points.is_empty());
+        assert!(Arc::ptr_eq(&index, &get_index(&path, "server_log.txt", crc)));
+
+        // same entry with another mtime, eg: cloud save synced again
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000)).unwrap();
+        drop(file);
+        let new_index = get_index(&path, "server_log.txt", crc);
+        assert!(!Arc::ptr_eq(&index, &new_index));
+        assert!(new_index.lock().unwrap().points.is_empty());
+
+        // another content, old checkpoints must not be used
+        let other = sample(3 * SPAN as usize, 3);
+        write_zip(&path, &other);
+        let mut reader = open(&path);
+        assert_eq!(read_at(&mut reader, 2 * SPAN + 17, 1000), &other[2 * SPAN as usize + 17..2 * SPAN as usize + 1017]);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn range_reads_start_from_checkpoints() {
+        let path = temp_zip();
+        let data = sample(5 * SPAN as usize, 4);
+        write_zip(&path, &data);
+        let log = crate::LogPath::dst_cloud(&path, "server_log.txt");
+        let crate::LogData::Stream(mut reader) = log.read_data().unwrap() else {
+            panic!("large cloud log is buffered");
+        };
+        // a full read builds checkpoints and checks crc
+        reader.read_to_end(&mut vec![]).unwrap();
+
+        // break the beginning of the entry, a read which inflates from offset 0 can't succeed
+        let data_start = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap()
+            .by_name("server_log.txt").unwrap().data_start();
+        let mtime = File::open(&path).unwrap().metadata().unwrap().modified().unwrap();
+        let mut file = File::options().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(data_start)).unwrap();
+        file.write_all(&[0xff; 64]).unwrap();
+        file.set_modified(mtime).unwrap();
+        drop(file);
+
+        let (start, end, text) = log.read_line_range(4 * SPAN, 4 * SPAN + 100).unwrap();
+        assert!(start == 4 * SPAN && end > start + 100);
+        assert_eq!(text, &data[start as usize..end as usize]);
+        assert!(log.open().unwrap().read_to_end(&mut vec![]).is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn crc_mismatch_is_invalid_data() {
+        let path = temp_zip();
+        let data = sample(SPAN as usize, 5);
+        write_zip(&path, &data);
+        let mut reader = open(&path);
+        let LogReader::ForDeflatedEntry(inflate) = &mut reader else { unreachable!() };
+        inflate.crc32 ^= 1;
+        let e = reader.read_to_end(&mut vec![]).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    fn crc32(path: &Path) -> u32 {
+        let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+        let crc = archive.by_name("server_log.txt").unwrap().crc32();
+        crc
+    }
+}
@@ -0,0 +1,115 @@
+// Typed payloads of tauri commands.
+// TypeScript definitions in `src/bindings/` are generated from these types by the app's build script,
+// do not edit them by hand.
+use std::path::Path;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::cluster::ClusterConfig;
+use crate::discovery::{DiscoveryRoot, SkippedLog};
+use crate::error::LogError;
+use crate::modinfo::ModMeta;
+use crate::parser::LogComment;
+use crate::redact::RedactItem;
+use crate::related::RelatedFile;
+use crate::scripts::SourceSnippet;
+use crate::tree::LogTreeNode;
+
+/// a log file in `list_all_logs`, also injected to log window as `window.logPath`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogInfo {
+    /// ds | dst | dyn
+    pub game: String,
+    pub filename: String,
+    /// local file, or url of remote log
+    pub filepath: String,
+    /// unix timestamp in seconds, -1 if not available
+    pub mtime: f64,
+    #[ts(type = "number")]
+    pub filesize: u64,
+    pub is_zip: bool,
+    /// serialized `LogPath`, pass it back to `open_log` to open the exact entry
+    pub source: String,
+}
+
+/// result of `list_all_logs`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogList {
+    pub logs: Vec<LogInfo>,
+    /// found but not readable, eg: a corrupted cloud save
+    pub skipped: Vec<SkippedLog>,
+}
+
+/// result of `list_log_tree`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogTree {
+    pub nodes: Vec<LogTreeNode>,
+    pub skipped: Vec<SkippedLog>,
+}
+
+/// payload of `archive-logs` event, sent when an opened archive has more than one log
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ArchiveLogs {
+    pub filepath: String,
+    pub logs: Vec<LogInfo>,
+}
+
+/// result of `load_log_abstract`, a quick scan for the log list
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogAbstract {
+    pub filename: String,
+    pub filepath: String,
+    pub is_zip: bool,
+    /// [hour, minute, second] of the last line
+    pub total_time: [u32; 3],
+    pub has_lua_crash: bool,
+}
+
+/// result of `load_log_init`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogInitData {
+    pub label: String,
+    pub active: bool,
+    pub exists: bool,
+    pub mtime: f64,
+    /// why the log can not be read or parsed, the comment is empty in this case
+    pub error: Option<LogError>,
+    pub comment: LogComment,
+    /// config of the cluster and shard, server logs in cluster folders only
+    pub cluster: Option<ClusterConfig>,
+    /// content length in bytes, the text is read by `load_log_range`
+    #[ts(type = "number")]
+    pub size: u64,
+}
+
+/// result of `load_log_range`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogRange {
+    pub start: usize,
+    /// end of the last line, start of the next range
+    pub end: usize,
+    pub text: String,
+}
+
+/// result of `preview_redaction`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RedactionReport {
+    pub items: Vec<RedactItem>,
+}
+
+/// write TypeScript definitions of all IPC types (and the types they use) to `dir`
+pub fn export_bindings(dir: impl AsRef<Path>) -> Result<(), String> {
+    let dir = dir.as_ref();
+    let result = LogList::export_all_to(dir)
+        .and_then(|_| LogTree::export_all_to(dir))
+        .and_then(|_| LogAbstract::export_all_to(dir))
+        .and_then(|_| ArchiveLogs::export_all_to(dir))
+        .and_then(|_| DiscoveryRoot::export_all_to(dir))
+        .and_then(|_| LogInitData::export_all_to(dir))
+        .and_then(|_| LogRange::export_all_to(dir))
+        .and_then(|_| RelatedFile::export_all_to(dir))
+        .and_then(|_| ModMeta::export_all_to(dir))
+        .and_then(|_| SourceSnippet::export_all_to(dir))
+        .and_then(|_| RedactionReport::export_all_to(dir));
+    result.map_err(|e| format!("failed to export bindings: {}", e))
+}
@@ -0,0 +1,75 @@
+//! Don't Starve / Don't Starve Together log discovery and parser.
+//!
+//! This crate does not depend on Tauri, it is shared by the log reader app,
+//! the `ds-log` command line tool and other programs which need to read game logs.
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use ds_log_core::{collect_logs, parse_log_content, LogPath};
+//!
+//! // find all logs under `<document_dir>/Klei`
+//! let (logs, _skipped) = collect_logs(Path::new("/home/me/Documents"));
+//! for log in logs {
+//!     println!("{}", log.get_menu_path());
+//! }
+//!
+//! // parse a log file, or any other `Read`
+//! let log = LogPath::external("client_log.txt");
+//! let comment = parse_log_content(log.open().unwrap()).unwrap();
+//! for error in comment.errors.iter() {
+//!     println!("line {}: {}", error.line, error.message);
+//! }
+//! ```
+
+/// Error type of all fallible operations.
+pub mod error;
+/// Pluggable log sources.
+pub mod source;
+/// Log file location and helpers for file metadata.
+pub mod path;
+/// Unified reader for plain files and zip entries.
+pub mod reader;
+/// Seekable reader for deflated zip entries.
+pub mod inflate;
+/// Line based parser which builds `LogComment`.
+pub mod parser;
+/// Logs inside zip, gzip and tar archives, including nested ones.
+pub mod archive;
+/// Remote logs over HTTP(S).
+pub mod remote;
+/// Logs from pasted text.
+pub mod memory;
+/// Steam install and library folders.
+pub mod steam;
+/// Find log files from built-in and user-added root directories.
+pub mod discovery;
+/// Constant Lua tables, eg: `modoverrides.lua`.
+pub mod lua;
+/// Cluster folders, `cluster.ini`, `server.ini` and `modoverrides.lua`.
+pub mod cluster;
+/// Discovered logs grouped by game, branch, user, cluster and shard.
+pub mod tree;
+/// Save and cluster files related to a log.
+pub mod related;
+/// Metadata of installed mods from `modinfo.lua`.
+pub mod modinfo;
+/// Game Lua sources referenced by Lua errors.
+pub mod scripts;
+/// Crash summary in Markdown, BBCode or plain text.
+pub mod summary;
+/// Hide private information before sharing.
+pub mod redact;
+/// Versioned JSON and CSV export.
+pub mod export;
+/// Typed payloads of tauri commands, and TypeScript bindings.
+pub mod ipc;
+/// Headless command line interface.
+pub mod cli;
+
+pub use error::LogError;
+pub use source::{register_source, LogSource, SourceMeta};
+pub use path::{LogPath, PathExt};
+pub use reader::{LogContent, LogData, LogReader, ZipIndex};
+pub use parser::{parse_log_content, DsDlc, DsInfo, LogComment, LogField, LogKind, LuaError, Mod, ModDiagnostic, ModLoadProfile, ModProblemKind, Session, StorageRefs, SystemInfo, SystemWarning};
+pub use discovery::{builtin_roots, collect_logs, collect_logs_from, DiscoveryRoot, RootKind, SkippedLog};
+pub use tree::{build_log_tree, LogTreeNode, NodeKind};
@@ -0,0 +1,513 @@
+// Parser for Lua table literals, eg: `return { ["workshop-123"]={ enabled=true } }`
+// Only constant expressions and string concatenation are supported, which is enough for
+// modoverrides.lua, leveldataoverride.lua and modinfo.lua fields. Lua code is never executed,
+// anything else (calls, variables) is a parse error.
+use crate::error::LogError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LuaValue {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    /// fields in source order, positional fields have number keys starting from 1
+    Table(Vec<(LuaValue, LuaValue)>),
+}
+
+impl LuaValue {
+    /// field of a table by string key
+    pub fn get(&self, key: &str) -> Option<&LuaValue> {
+        self.fields().iter()
+            .find(|(k, _)| matches!(k, LuaValue::String(s) if s == key))
+            .map(|(_, v)| v)
+    }
+
+    pub fn fields(&self) -> &[(LuaValue, LuaValue)] {
+        match self {
+            LuaValue::Table(fields) => fields,
+            _ => &[],
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            LuaValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            LuaValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            LuaValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// short text for display, tables are shown as `{...}`
+    pub fn to_display(&self) -> String {
+        match self {
+            LuaValue::Nil => "nil".to_string(),
+            LuaValue::Bool(b) => b.to_string(),
+            LuaValue::Number(n) => n.to_string(),
+            LuaValue::String(s) => s.clone(),
+            LuaValue::Table(fields) if fields.is_empty() => "{}".to_string(),
+            LuaValue::Table(_) => "{...}".to_string(),
+        }
+    }
+}
+
+/// parse a chunk like `return {...}`, a bare expression is accepted as well
+pub fn parse_lua_table(source: &str) -> Result<LuaValue, LogError> {
+    let mut parser = Parser { src: source.as_bytes(), pos: 0, lenient: false, depth: 0 };
+    parser.skip_space()?;
+    if parser.peek_word() == Some("return") {
+        parser.pos += "return".len();
+    }
+    let value = parser.value()?;
+    parser.skip_space()?;
+    if parser.src.get(parser.pos) == Some(&b';') {
+        parser.pos += 1;
+        parser.skip_space()?;
+    }
+    if parser.pos < parser.src.len() {
+        return Err(parser.error("unexpected content after value"));
+    }
+    Ok(value)
+}
+
+/// top-level `name = value` statements of a script like modinfo.lua,
+/// statements which are not constant are skipped, the first assignment of a name wins,
+/// table fields which are not constant (eg: function calls) are read as `nil`
+pub fn parse_lua_assignments(source: &str) -> Vec<(String, LuaValue)> {
+    let mut parser = Parser { src: source.as_bytes(), pos: 0, lenient: true, depth: 0 };
+    let mut result: Vec<(String, LuaValue)> = vec![];
+    while parser.pos < parser.src.len() {
+        if parser.skip_space().is_err() {
+            break;
+        }
+        let line_start = parser.pos;
+        if let Some(name) = parser.peek_word().map(str::to_string) {
+            parser.pos += name.len();
+            let _ = parser.skip_space();
+            let is_assign = parser.peek() == Some(b'=') && parser.src.get(parser.pos + 1) != Some(&b'=');
+            if is_assign && name != "local" {
+                parser.pos += 1;
+                let value_start = parser.pos;
+                if let Ok(value) = parser.value() {
+                    if !result.iter().any(|(k, _)| *k == name) {
+                        result.push((name, value));
+                    }
+                    continue;
+                }
+                // skip the whole table, so that its fields are not taken as top-level names
+                parser.pos = value_start;
+                if parser.skip_space().is_ok() && parser.peek() == Some(b'{') && parser.skip_table() {
+                    continue;
+                }
+            }
+        }
+        // skip to next line
+        parser.pos = line_start;
+        while parser.peek().is_some_and(|b| b != b'\n') {
+            parser.pos += 1;
+        }
+    }
+    result
+}
+
+/// nesting limit of tables and unary minus, deeper values are a parse error instead of a stack overflow
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    /// read non-constant table fields as `nil` instead of failing
+    lenient: bool,
+    /// current nesting of `single_value`
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> LogError {
+        let line = self.src[..self.pos.min(self.src.len())].iter().filter(|b| **b == b'\n').count() + 1;
+        LogError::Parse(format!("line {}: {}", line, message))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn peek_word(&self) -> Option<&str> {
+        let rest = &self.src[self.pos..];
+        let len = rest.iter().take_while(|b| b.is_ascii_alphanumeric() || **b == b'_').count();
+        if len == 0 || rest[0].is_ascii_digit() {
+            return None;
+        }
+        std::str::from_utf8(&rest[..len]).ok()
+    }
+
+    /// skip whitespace and comments
+    fn skip_space(&mut self) -> Result<(), LogError> {
+        loop {
+            while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+                self.pos += 1;
+            }
+            if !self.src[self.pos..].starts_with(b"--") {
+                return Ok(());
+            }
+            self.pos += 2;
+            if self.long_bracket_level().is_some() {
+                self.long_string()?;
+            }
+            else {
+                while self.peek().is_some_and(|b| b != b'\n') {
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// move to the end of a table which can not be parsed, returns false if it is not closed
+    fn skip_table(&mut self) -> bool {
+        let mut depth = 0;
+        while let Some(b) = self.peek() {
+            match b {
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.pos += 1;
+                        return true;
+                    }
+                },
+                b'"' | b'\'' => {
+                    if self.quoted_string().is_err() {
+                        return false;
+                    }
+                    continue;
+                },
+                b'[' if self.long_bracket_level().is_some() => {
+                    if self.long_string().is_err() {
+                        return false;
+                    }
+                    continue;
+                },
+                b'-' if self.src[self.pos..].starts_with(b"--") => {
+                    if self.skip_space().is_err() {
+                        return false;
+                    }
+                    continue;
+                },
+                _ => {},
+            }
+            self.pos += 1;
+        }
+        false
+    }
+
+    /// move to the next `,`, `;` or `}` outside of brackets and strings
+    fn skip_expression(&mut self) -> Result<(), LogError> {
+        let mut depth = 0;
+        loop {
+            self.skip_space()?;
+            let b = self.peek().ok_or_else(|| self.error("unexpected end of file"))?;
+            match b {
+                b',' | b';' | b'}' if depth == 0 => return Ok(()),
+                b'(' | b'{' => depth += 1,
+                b'[' if self.long_bracket_level().is_none() => depth += 1,
+                b')' | b'}' | b']' => depth -= 1,
+                b'"' | b'\'' => {
+                    self.quoted_string()?;
+                    continue;
+                },
+                b'[' => {
+                    self.long_string()?;
+                    continue;
+                },
+                _ => {},
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// value of a table field, see `lenient`
+    fn field_value(&mut self) -> Result<LuaValue, LogError> {
+        let start = self.pos;
+        match self.value() {
+            Err(_) if self.lenient => {
+                self.pos = start;
+                self.skip_expression()?;
+                Ok(LuaValue::Nil)
+            },
+            result => result,
+        }
+    }
+
+    /// level of `[==[` at current position
+    fn long_bracket_level(&self) -> Option<usize> {
+        let rest = &self.src[self.pos..];
+        if rest.first() != Some(&b'[') {
+            return None;
+        }
+        let level = rest[1..].iter().take_while(|b| **b == b'=').count();
+        (rest.get(level + 1) == Some(&b'[')).then_some(level)
+    }
+
+    fn long_string(&mut self) -> Result<String, LogError> {
+        let level = self.long_bracket_level().ok_or_else(|| self.error("expected long bracket"))?;
+        self.pos += level + 2;
+        // a newline right after the opening bracket is skipped
+        if self.peek() == Some(b'\r') { self.pos += 1; }
+        if self.peek() == Some(b'\n') { self.pos += 1; }
+        let close = format!("]{}]", "=".repeat(level));
+        let start = self.pos;
+        let len = self.src[start..].windows(close.len()).position(|w| w == close.as_bytes())
+            .ok_or_else(|| self.error("unfinished long string"))?;
+        self.pos = start + len + close.len();
+        Ok(String::from_utf8_lossy(&self.src[start..start + len]).to_string())
+    }
+
+    fn quoted_string(&mut self) -> Result<String, LogError> {
+        let quote = self.src[self.pos];
+        self.pos += 1;
+        let mut buf = vec![];
+        loop {
+            let b = self.peek().ok_or_else(|| self.error("unfinished string"))?;
+            self.pos += 1;
+            match b {
+                b'\n' => return Err(self.error("unfinished string")),
+                b'\\' => {
+                    let e = self.peek().ok_or_else(|| self.error("unfinished string"))?;
+                    self.pos += 1;
+                    match e {
+                        b'n' => buf.push(b'\n'),
+                        b't' => buf.push(b'\t'),
+                        b'r' => buf.push(b'\r'),
+                        b'a' => buf.push(7),
+                        b'b' => buf.push(8),
+                        b'f' => buf.push(12),
+                        b'v' => buf.push(11),
+                        b'\n' => buf.push(b'\n'),
+                        b'0'..=b'9' => {
+                            let mut n = (e - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'9') => {
+                                        n = n * 10 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    },
+                                    _ => break,
+                                }
+                            }
+                            buf.push(n.min(255) as u8);
+                        },
+                        other => buf.push(other),
+                    }
+                },
+                b if b == quote => break,
+                b => buf.push(b),
+            }
+        }
+        Ok(String::from_utf8_lossy(&buf).to_string())
+    }
+
+    fn number(&mut self) -> Result<f64, LogError> {
+        let start = self.pos;
+        let rest = &self.src[start..];
+        if rest.starts_with(b"0x") || rest.starts_with(b"0X") {
+            let len = rest[2..].iter().take_while(|b| b.is_ascii_hexdigit()).count();
+            self.pos += 2 + len;
+            let s = std::str::from_utf8(&rest[2..2 + len]).unwrap_or_default();
+            return u64::from_str_radix(s, 16).map(|n| n as f64).map_err(|_| self.error("invalid number"));
+        }
+        while let Some(b) = self.peek() {
+            // sign of exponent, eg: 1e-5
+            let sign = (b == b'-' || b == b'+') && self.pos > start
+                && matches!(self.src[self.pos - 1], b'e' | b'E');
+            if b.is_ascii_digit() || b == b'.' || b == b'e' || b == b'E' || sign {
+                self.pos += 1;
+            }
+            else {
+                break;
+            }
+        }
+        std::str::from_utf8(&self.src[start..self.pos]).ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| self.error("invalid number"))
+    }
+
+    fn value(&mut self) -> Result<LuaValue, LogError> {
+        let mut value = self.single_value()?;
+        // "a" .. "b" .. "c"
+        loop {
+            self.skip_space()?;
+            if !self.src[self.pos..].starts_with(b"..") {
+                return Ok(value);
+            }
+            self.pos += 2;
+            let left = match value {
+                LuaValue::String(s) => s,
+                LuaValue::Number(n) => n.to_string(),
+                _ => return Err(self.error("invalid concatenation")),
+            };
+            value = match self.single_value()? {
+                LuaValue::String(s) => LuaValue::String(left + &s),
+                LuaValue::Number(n) => LuaValue::String(left + &n.to_string()),
+                _ => return Err(self.error("invalid concatenation")),
+            };
+        }
+    }
+
+    fn single_value(&mut self) -> Result<LuaValue, LogError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(&format!("nested deeper than {} levels", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let result = self.single_value_impl();
+        self.depth -= 1;
+        result
+    }
+
+    fn single_value_impl(&mut self) -> Result<LuaValue, LogError> {
+        self.skip_space()?;
+        match self.peek() {
+            None => Err(self.error("unexpected end of file")),
+            Some(b'{') => self.table(),
+            Some(b'"') | Some(b'\'') => Ok(LuaValue::String(self.quoted_string()?)),
+            Some(b'[') if self.long_bracket_level().is_some() => Ok(LuaValue::String(self.long_string()?)),
+            Some(b'-') => {
+                self.pos += 1;
+                self.skip_space()?;
+                match self.single_value()? {
+                    LuaValue::Number(n) => Ok(LuaValue::Number(-n)),
+                    _ => Err(self.error("expected number after `-`")),
+                }
+            },
+            Some(b) if b.is_ascii_digit() || b == b'.' => Ok(LuaValue::Number(self.number()?)),
+            Some(_) => {
+                let word = self.peek_word().ok_or_else(|| self.error("unexpected character"))?;
+                let value = match word {
+                    "true" => LuaValue::Bool(true),
+                    "false" => LuaValue::Bool(false),
+                    "nil" => LuaValue::Nil,
+                    _ => return Err(self.error(&format!("unsupported expression: {}", word))),
+                };
+                self.pos += word.len();
+                Ok(value)
+            },
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), LogError> {
+        self.skip_space()?;
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            Ok(())
+        }
+        else {
+            Err(self.error(&format!("expected `{}`", b as char)))
+        }
+    }
+
+    fn table(&mut self) -> Result<LuaValue, LogError> {
+        self.expect(b'{')?;
+        let mut fields = vec![];
+        let mut index = 1;
+        loop {
+            self.skip_space()?;
+            match self.peek() {
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(LuaValue::Table(fields));
+                },
+                Some(b'[') if self.long_bracket_level().is_none() => {
+                    self.pos += 1;
+                    let key = self.value()?;
+                    self.expect(b']')?;
+                    self.expect(b'=')?;
+                    fields.push((key, self.field_value()?));
+                },
+                _ => {
+                    // `name = value` or a positional value
+                    let word = self.peek_word().map(str::to_string);
+                    let named = match word.as_deref() {
+                        Some("true") | Some("false") | Some("nil") | None => None,
+                        Some(word) => {
+                            let save = self.pos;
+                            self.pos += word.len();
+                            self.skip_space()?;
+                            if self.peek() == Some(b'=') {
+                                self.pos += 1;
+                                Some(word.to_string())
+                            }
+                            else {
+                                self.pos = save;
+                                None
+                            }
+                        }
+                    };
+                    match named {
+                        Some(name) => fields.push((LuaValue::String(name), self.field_value()?)),
+                        None => {
+                            fields.push((LuaValue::Number(index as f64), self.field_value()?));
+                            index += 1;
+                        }
+                    }
+                },
+            }
+            self.skip_space()?;
+            match self.peek() {
+                Some(b',') | Some(b';') => self.pos += 1,
+                Some(b'}') => {},
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_table() {
+        let value = parse_lua_table(r#"
+return {
+    -- comment
+    ["workshop-123"] = { enabled = true, configuration_options = { speed = -1.5, [ [[a]] ] = "x" .. 1 .. 'y' } },
+    "first", nil, 3;
+}"#).unwrap();
+        let options = value.get("workshop-123").unwrap().get("configuration_options").unwrap();
+        assert_eq!(options.get("speed"), Some(&LuaValue::Number(-1.5)));
+        assert_eq!(options.get("a").and_then(LuaValue::as_str), Some("x1y"));
+        assert_eq!(value.fields()[1], (LuaValue::Number(1.0), LuaValue::String("first".into())));
+        assert_eq!(value.fields()[3], (LuaValue::Number(3.0), LuaValue::Number(3.0)));
+        assert!(parse_lua_table("return { a = f() }").is_err());
+        assert!(parse_lua_table("{} x").is_err());
+    }
+
+    #[test]
+    fn depth_is_limited() {
+        let nested = |n: usize| format!("{}{}", "{".repeat(n), "}".repeat(n));
+        assert!(parse_lua_table(&nested(MAX_DEPTH)).is_ok());
+        for source in [nested(MAX_DEPTH + 1), nested(100_000), format!("{}1", "- ".repeat(100_000))] {
+            match parse_lua_table(&source) {
+                Err(LogError::Parse(message)) => assert!(message.contains("nested deeper")),
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+        let concat = vec!["'a'"; 10_000].join("..");
+        assert_eq!(parse_lua_table(&concat).unwrap().as_str().map(str::len), Some(10_000));
+        // lenient parsing reads the too deep field as nil, later names are still read
+        let assignments = parse_lua_assignments(&format!("a = {}\nb = 1\n", nested(1000)));
+        assert_eq!(assignments.len(), 2);
+        assert_eq!(assignments[1], ("b".to_string(), LuaValue::Number(1.0)));
+    }
+}
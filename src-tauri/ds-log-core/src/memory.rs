@@ -0,0 +1,92 @@
+// In-memory log, eg: a fragment pasted from Discord
+// It lives as long as its window and is never saved to recent files.
+use std::io::Cursor;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::LogError;
+use crate::reader::LogReader;
+use crate::source::{LogSource, SourceMeta};
+
+#[derive(Debug, Clone)]
+pub struct MemorySource {
+    id: String,
+    name: String,
+    content: Arc<[u8]>,
+    /// unix timestamp in seconds
+    created: f64,
+}
+
+impl MemorySource {
+    /// `name` is used as window title and export file name, default: `pasted-<time>.txt`
+    pub fn new(name: Option<String>, content: impl Into<Vec<u8>>) -> Self {
+        let now = chrono::Local::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name: name.unwrap_or_else(|| format!("pasted-{}.txt", now.format("%Y%m%d-%H%M%S"))),
+            content: content.into().into(),
+            created: now.timestamp_millis() as f64 / 1000.0,
+        }
+    }
+}
+
+impl LogSource for MemorySource {
+    fn kind(&self) -> &'static str {
+        "memory"
+    }
+
+    fn label(&self) -> String {
+        format!("memory-{}", self.id)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self) -> String {
+        format!("<{}>", self.name)
+    }
+
+    fn open(&self) -> Result<LogReader, LogError> {
+        // no copy, the content is shared with every reader
+        Ok(LogReader::ForShared(Cursor::new(self.content.clone())))
+    }
+
+    fn metadata(&self) -> SourceMeta {
+        SourceMeta {
+            exists: true,
+            mtime: self.created,
+            size: self.content.len() as u64,
+        }
+    }
+
+    fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            "id": self.id.clone(),
+            "name": self.name.clone(),
+        }
+    }
+
+    /// content never changes
+    fn version(&self) -> Option<String> {
+        Some(self.id.clone())
+    }
+
+    fn is_persistent(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_shares_content() {
+        let source = MemorySource::new(None, "[00:00:01]: pasted\n");
+        let content = source.open().unwrap().into_content().unwrap();
+        let crate::LogContent::Shared(content) = content else { panic!("content is copied") };
+        assert!(Arc::ptr_eq(&content, &source.content));
+        assert!(source.name.starts_with("pasted-"));
+    }
+}
@@ -0,0 +1,213 @@
+// Metadata of installed mods from `modinfo.lua`, without network access
+// A mod is searched in Steam workshop content (`workshop/content/<appid>/<id>`) and `mods` dirs
+// of game installs. modinfo.lua is read by `parse_lua_assignments`, it is never executed, so
+// values computed by code (eg: translated descriptions) are missing.
+use std::fs;
+use std::path::PathBuf;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::cluster::ConfigOption;
+use crate::lua::{parse_lua_assignments, LuaValue};
+use crate::parser::Mod;
+use crate::steam::{app_id_for_game, game_mods_dirs, workshop_content_dirs};
+
+/// an item of `configuration_options`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ModConfigOption {
+    pub name: String,
+    pub label: Option<String>,
+    pub default: Option<String>,
+    /// description and data of each choice
+    pub choices: Vec<ConfigOption>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct ModMeta {
+    /// eg: workshop-727774324
+    pub moddir: String,
+    /// mod folder on local disk
+    pub path: String,
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    pub api_version: Option<u32>,
+    pub dst_compatible: Option<bool>,
+    pub client_only_mod: Option<bool>,
+    pub all_clients_require_mod: Option<bool>,
+    pub server_only_mod: Option<bool>,
+    /// moddir or name of required mods, from `mod_dependencies`
+    pub dependencies: Vec<String>,
+    pub options: Vec<ModConfigOption>,
+}
+
+/// dirs to search mods in, workshop content first
+pub struct ModDirs {
+    workshop: Vec<PathBuf>,
+    mods: Vec<PathBuf>,
+}
+
+impl ModDirs {
+    /// probe Steam libraries for a game type, eg: `dst`
+    pub fn probe(game_type: &str) -> Self {
+        let app_id = app_id_for_game(game_type);
+        Self { workshop: workshop_content_dirs(app_id), mods: game_mods_dirs(app_id) }
+    }
+
+    /// local folder of a mod, None if not installed
+    pub fn find(&self, moddir: &str) -> Option<PathBuf> {
+        let has_info = |dir: &PathBuf| dir.join("modinfo.lua").is_file();
+        if let Some(id) = moddir.strip_prefix("workshop-") {
+            if let Some(dir) = self.workshop.iter().map(|w| w.join(id)).find(has_info) {
+                return Some(dir);
+            }
+        }
+        self.mods.iter().map(|m| m.join(moddir)).find(has_info)
+    }
+}
+
+impl ModMeta {
+    /// read `<dir>/modinfo.lua`, None if it can not be read
+    pub fn load(moddir: &str, dir: PathBuf) -> Option<Self> {
+        let data = fs::read(dir.join("modinfo.lua")).ok()?;
+        let fields = parse_lua_assignments(&String::from_utf8_lossy(&data));
+        let get = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        let text = |key: &str| get(key).and_then(LuaValue::as_str)
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        let flag = |key: &str| get(key).and_then(LuaValue::as_bool);
+        Some(Self {
+            moddir: moddir.to_string(),
+            path: dir.to_string_lossy().to_string(),
+            name: text("name"),
+            author: text("author"),
+            description: text("description"),
+            version: get("version").filter(|v| !matches!(v, LuaValue::Table(_) | LuaValue::Nil)).map(LuaValue::to_display),
+            api_version: get("api_version").and_then(LuaValue::as_f64).map(|n| n as u32),
+            dst_compatible: flag("dst_compatible"),
+            client_only_mod: flag("client_only_mod"),
+            all_clients_require_mod: flag("all_clients_require_mod"),
+            server_only_mod: flag("server_only_mod"),
+            dependencies: get("mod_dependencies").map(parse_dependencies).unwrap_or_default(),
+            options: get("configuration_options").map(parse_options).unwrap_or_default(),
+        })
+    }
+}
+
+/// `mod_dependencies = { {workshop = "workshop-123"}, {["Mod Name"] = false} }`
+fn parse_dependencies(value: &LuaValue) -> Vec<String> {
+    let mut result = vec![];
+    for (_, dep) in value.fields() {
+        for (k, v) in dep.fields() {
+            let name = match (k, v) {
+                (_, LuaValue::String(s)) => s.clone(),
+                (LuaValue::String(s), LuaValue::Bool(_)) => s.clone(),
+                _ => continue,
+            };
+            if !result.contains(&name) {
+                result.push(name);
+            }
+        }
+    }
+    result
+}
+
+/// `configuration_options = { {name = "key", label = "Key", options = {{description = "A", data = 1}}, default = 1} }`
+fn parse_options(value: &LuaValue) -> Vec<ModConfigOption> {
+    let text = |v: &LuaValue, key| v.get(key).and_then(LuaValue::as_str).map(str::to_string);
+    value.fields().iter()
+        .filter_map(|(_, option)| Some(ModConfigOption {
+            name: text(option, "name")?,
+            label: text(option, "label").filter(|s| !s.is_empty()),
+            default: option.get("default").map(LuaValue::to_display),
+            choices: option.get("options").map(|o| o.fields()).unwrap_or_default().iter()
+                .filter(|(_, c)| matches!(c, LuaValue::Table(_)))
+                .map(|(_, c)| ConfigOption {
+                    name: c.get("description").map(LuaValue::to_display).unwrap_or_default(),
+                    value: c.get("data").map(LuaValue::to_display).unwrap_or_default(),
+                })
+                .collect(),
+        }))
+        .collect()
+}
+
+/// metadata of loaded mods which are installed on this machine, in the order of `mods`
+pub fn load_mod_meta<'a>(mods: impl IntoIterator<Item = &'a Mod>, game_type: &str) -> Vec<ModMeta> {
+    let dirs = ModDirs::probe(game_type);
+    mods.into_iter()
+        .filter_map(|m| ModMeta::load(&m.moddir, dirs.find(&m.moddir)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODINFO: &str = r#"
+name = "Craft Pot"
+description = STRINGS.DESCRIPTION .. "\n"
+author = "  Someone  "
+version = "0.15.0"
+api_version = 10
+dst_compatible = true
+client_only_mod = true
+all_clients_require_mod = false
+local function option(name) return {name = name} end
+mod_dependencies = {
+    {workshop = "workshop-378160973"},
+    {["Global Positions"] = false},
+}
+configuration_options = {
+    {
+        name = "lang",
+        label = "Language",
+        options = {
+            {description = "English", data = "en"},
+            {description = "Auto", data = false},
+        },
+        default = "en",
+    },
+    {name = "", label = "", options = {}, default = 0},
+    option("computed"),
+    {label = "no name"},
+}
+"#;
+
+    #[test]
+    fn find_and_load() {
+        let root = std::env::temp_dir().join(format!("ds-log-modinfo-{}", uuid::Uuid::new_v4()));
+        let workshop = root.join("workshop/content/322330");
+        let mods = root.join("common/mods");
+        for dir in [workshop.join("727774324"), mods.join("workshop-727774324"), mods.join("local_mod"), mods.join("broken")] {
+            fs::create_dir_all(&dir).unwrap();
+        }
+        fs::write(workshop.join("727774324/modinfo.lua"), MODINFO).unwrap();
+        fs::write(mods.join("workshop-727774324/modinfo.lua"), "name = \"old copy\"").unwrap();
+        fs::write(mods.join("local_mod/modinfo.lua"), "name = \"Local\"\nversion = 1.5").unwrap();
+        let dirs = ModDirs { workshop: vec![workshop.clone()], mods: vec![mods.clone()] };
+
+        // workshop content wins over the `mods` dir
+        assert_eq!(dirs.find("workshop-727774324"), Some(workshop.join("727774324")));
+        assert_eq!(dirs.find("local_mod"), Some(mods.join("local_mod")));
+        assert_eq!(dirs.find("broken"), None);
+
+        let meta = ModMeta::load("workshop-727774324", dirs.find("workshop-727774324").unwrap()).unwrap();
+        assert_eq!(meta.name.as_deref(), Some("Craft Pot"));
+        assert_eq!(meta.author.as_deref(), Some("Someone"));
+        assert_eq!(meta.description, None);
+        assert_eq!(meta.version.as_deref(), Some("0.15.0"));
+        assert_eq!(meta.api_version, Some(10));
+        assert_eq!((meta.dst_compatible, meta.client_only_mod, meta.all_clients_require_mod, meta.server_only_mod),
+            (Some(true), Some(true), Some(false), None));
+        assert_eq!(meta.dependencies, vec!["workshop-378160973", "Global Positions"]);
+        let options = meta.options.iter().map(|o| (o.name.as_str(), o.label.as_deref(), o.default.as_deref())).collect::<Vec<_>>();
+        assert_eq!(options, vec![("lang", Some("Language"), Some("en")), ("", None, Some("0"))]);
+        let choices = meta.options[0].choices.iter().map(|c| (c.name.as_str(), c.value.as_str())).collect::<Vec<_>>();
+        assert_eq!(choices, vec![("English", "en"), ("Auto", "false")]);
+
+        let local = ModMeta::load("local_mod", mods.join("local_mod")).unwrap();
+        assert_eq!(local.version.as_deref(), Some("1.5"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,1403 @@
+// DST log parser
+// TODO: 大文件懒解析
+// TODO: 多线程解析
+use std::collections::HashMap;
+use std::io::Read;
+use indexmap::IndexMap;
+use lines::{read_lines, linereader::LineReader};
+use regex::Regex;
+use once_cell::sync::Lazy;
+use serde::{Serialize, Serializer};
+use ts_rs::TS;
+
+use crate::error::LogError;
+use crate::path::LogPath;
+
+/// Max length of a line in log file.
+/// Too long line will be skipped parsing.
+static MAX_LINE_LEN: usize = 2000;
+
+/// self held loading state for LogComment
+#[derive(Debug, Clone, Default)]
+pub struct LogState {
+    current_field_name: String,
+    current_field_line: usize,
+    current_line: usize,
+    /// previous line content (time prefix stripped)
+    last_line: String,
+    /// (moddir, problem) in order of appearance
+    mod_problems: Vec<(String, ModProblem)>,
+    mod_timer: Option<ModTimer>,
+    /// server markers, see `LogKind`
+    server: bool,
+    /// shard started by a client-hosted game, with `-monitor_parent_process`
+    child_shard: bool,
+    worldgen: bool,
+    /// the game reached a playable world
+    playing: bool,
+    /// collected for `LogComment.ds`
+    ds: DsInfo,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[ts(rename = "ModInfo")]
+pub struct Mod {
+    pub moddir: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub workshop_id: Option<String>,
+}
+
+/// load order and load time of a mod
+/// eg: Loading mod: workshop-727774324 (Craft Pot) Version:0.15.0
+///     Mod: workshop-727774324 (Craft Pot)\tLoading modmain.lua
+///     Mod: workshop-727774324 (Craft Pot)\tRegistering prefabs
+///     Mod: workshop-727774324 (Craft Pot)\t  Registering prefab file: prefabs/foodcooked
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct ModLoadProfile {
+    pub moddir: String,
+    pub name: String,
+    /// 1-based load order, by first appearance in `Loading mod:` or `Mod:` lines
+    pub order: usize,
+    /// seconds spent in modworldgenmain.lua and modmain.lua, summed if the mod is loaded more than once
+    pub load_seconds: u32,
+    /// seconds spent in registering prefabs
+    pub prefab_seconds: u32,
+    pub prefab_files: u32,
+    pub prefabs: u32,
+}
+
+/// what a mod is doing, see `ModTimer`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ModPhase {
+    Main,
+    Prefabs,
+}
+
+/// a mod which is running modmain.lua or registering prefabs
+#[derive(Debug, Clone)]
+struct ModTimer {
+    moddir: String,
+    phase: ModPhase,
+    /// seconds from log start
+    start: u32,
+    /// time of the last line printed with the `Mod: <moddir>` prefix
+    last_seen: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ModProblemKind {
+    /// registered by ModIndex, but `Loading mod:` is never printed
+    NotLoaded,
+    /// eg: Disabling workshop-123 (Name) because it had an error.
+    DisabledForCrash,
+    /// api_version of modinfo.lua is too old or in the future
+    OutdatedApi,
+    MissingDependency,
+    /// `[Workshop]` download or update failure
+    WorkshopFailure,
+    /// eg: Mod workshop-123 is out of date
+    OutOfDate,
+    /// eg: Error loading mod: workshop-123 (Name)!
+    LoadError,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ModProblem {
+    pub kind: ModProblemKind,
+    /// line number of the reason, 0 for `NotLoaded`
+    pub line: usize,
+    /// reason line without time prefix
+    pub message: String,
+}
+
+/// why a mod was not loaded, or has problems while loading
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ModDiagnostic {
+    pub moddir: String,
+    /// from `Loading mod:` line, None if never loaded
+    pub name: Option<String>,
+    pub loaded: bool,
+    pub problems: Vec<ModProblem>,
+}
+
+/// split a command line by whitespace, double quotes keep spaces, eg: `-persistent_storage_root "C:\My Server" -cluster Cluster_1`
+pub fn split_command(command: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut has_arg = false;
+    for c in command.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                has_arg = true;
+            },
+            c if c.is_whitespace() && !quoted => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            },
+            c => {
+                current.push(c);
+                has_arg = true;
+            },
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// save locations printed in log, used to find related files
+/// eg: PersistRootStorage is now APP:Klei//DoNotStarveTogether/123456/
+///     About to start a shard with these parameters: ... -conf_dir DoNotStarveTogether -cluster Cluster_1 -shard Caves
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct StorageRefs {
+    /// path after `APP:`, eg: Klei//DoNotStarveTogether/123456/
+    pub persist_roots: Vec<String>,
+    /// `-persistent_storage_root` of shard command line
+    pub storage_root: Option<String>,
+    pub conf_dir: Option<String>,
+    /// eg: Cluster_1
+    pub cluster: Option<String>,
+    /// eg: Master, Caves
+    pub shards: Vec<String>,
+    /// world session ids, eg: 3C8B4D1F1F61F9E5
+    pub sessions: Vec<String>,
+}
+
+impl StorageRefs {
+    fn push_unique(list: &mut Vec<String>, value: &str) {
+        if !list.iter().any(|v| v == value) {
+            list.push(value.to_string());
+        }
+    }
+
+    /// `-key value` pairs of a command line
+    fn parse_args(&mut self, args: &str) {
+        let mut iter = split_command(args).into_iter();
+        while let Some(arg) = iter.next() {
+            let field = match arg.as_str() {
+                "-persistent_storage_root" => &mut self.storage_root,
+                "-conf_dir" => &mut self.conf_dir,
+                "-cluster" => &mut self.cluster,
+                "-shard" => {
+                    if let Some(shard) = iter.next() {
+                        Self::push_unique(&mut self.shards, &shard);
+                    }
+                    continue;
+                },
+                _ => continue,
+            };
+            if let Some(value) = iter.next() {
+                *field = Some(value);
+            }
+        }
+    }
+}
+
+/// a game instance, starts from `cGame::StartPlaying`
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct Session {
+    pub start: usize,
+    /// last line of this session, 0 if not finished
+    pub end: usize,
+    /// time prefix of the first line, eg: [0, 0, 1]
+    pub start_time: Vec<u32>,
+}
+
+/// Lua error block, message line followed by traceback lines
+/// eg: [00:01:10]: [string "scripts/widgets/text.lua"]:45: attempt to index a nil value
+///     LUA ERROR stack traceback:
+///     scripts/widgets/text.lua:45 in (method) SetString (Lua) <40-60>
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct LuaError {
+    /// line number of the error message
+    pub line: usize,
+    pub message: String,
+    /// raw traceback lines, including local variable dumps
+    pub traceback: Vec<String>,
+}
+
+impl LuaError {
+    /// traceback lines without local variable dumps, eg: `   self = ...`
+    pub fn frames(&self) -> impl Iterator<Item = &str> {
+        self.traceback.iter()
+            .filter(|line| !line.starts_with([' ', '\t']))
+            .map(|line| line.as_str())
+    }
+
+    /// find the first mod directory in message and traceback
+    /// eg: ../mods/workshop-727774324/scripts/widgets/foodcrafting.lua:45 -> workshop-727774324
+    pub fn suspected_moddir(&self) -> Option<String> {
+        static MOD_SRC_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"\.\./mods/([^/]+)/").unwrap()
+        });
+        std::iter::once(self.message.as_str())
+            .chain(self.frames())
+            .find_map(|line| MOD_SRC_RE.captures(line))
+            .map(|m| m.get(1).unwrap().as_str().to_string())
+    }
+}
+
+fn utf8_first(s: &str, n: usize) -> &str {
+    for (i, (j, _)) in s.char_indices().enumerate() {
+        if i == n {
+            return &s[..j];
+        }
+    }
+    s
+}
+
+/// a content block with some information, eg: a Lua error with traceback
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct LogField {
+    pub start: usize,
+    pub end: usize,
+    /// eg: lua_crash
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub extra: String,
+}
+
+fn serialize_mods<S: Serializer>(mods: &IndexMap<String, Mod>, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_seq(mods.values())
+}
+
+/// which game process printed the log, detected from header and marker lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum LogKind {
+    #[default]
+    Unknown,
+    /// DS single-player, eg: `Don't Starve: 578406 OSX_STEAM`
+    Ds,
+    /// DST game client, eg: client_log.txt
+    DstClient,
+    /// DST shard started by a client-hosted game, eg: `Cluster_1/Caves/server_log.txt`
+    DstServer,
+    /// standalone DST dedicated server
+    Dedicated,
+    /// log which ends in world generation, before the world is played
+    Worldgen,
+}
+
+impl LogKind {
+    /// same as serialized form, eg: dst_client
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogKind::Unknown => "unknown",
+            LogKind::Ds => "ds",
+            LogKind::DstClient => "dst_client",
+            LogKind::DstServer => "dst_server",
+            LogKind::Dedicated => "dedicated",
+            LogKind::Worldgen => "worldgen",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogKind::Unknown => "unknown",
+            LogKind::Ds => "DS",
+            LogKind::DstClient => "DST client",
+            LogKind::DstServer => "DST server shard",
+            LogKind::Dedicated => "DST dedicated server",
+            LogKind::Worldgen => "world generation",
+        }
+    }
+
+    pub fn is_server(self) -> bool {
+        matches!(self, LogKind::DstServer | LogKind::Dedicated)
+    }
+}
+
+/// DLC of single-player DS
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum DsDlc {
+    /// DLC0001, REIGN_OF_GIANTS
+    ReignOfGiants,
+    /// DLC0002, CAPY_DLC
+    Shipwrecked,
+    /// DLC0003, PORKLAND_DLC
+    Hamlet,
+}
+
+impl DsDlc {
+    pub fn label(self) -> &'static str {
+        match self {
+            DsDlc::ReignOfGiants => "Reign of Giants",
+            DsDlc::Shipwrecked => "Shipwrecked",
+            DsDlc::Hamlet => "Hamlet",
+        }
+    }
+
+    /// `DLC0002` or `CAPY_DLC`, see `DsInfo`
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "DLC0001" | "REIGN_OF_GIANTS" => Some(DsDlc::ReignOfGiants),
+            "DLC0002" | "CAPY_DLC" => Some(DsDlc::Shipwrecked),
+            "DLC0003" | "PORKLAND_DLC" => Some(DsDlc::Hamlet),
+            _ => None,
+        }
+    }
+}
+
+/// launch info of single-player DS, only in DS log
+/// eg: DLC0002 enabled
+///     Loading world: shipwrecked_2
+///     Assert failure 'inst' at ..\source\simlib\entity.cpp(123): Trace follows...
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct DsInfo {
+    /// enabled DLCs, also implied by world type, eg: porkland -> Hamlet
+    pub dlcs: Vec<DsDlc>,
+    /// 1-based save slot of the last world
+    pub save_slot: Option<u32>,
+    /// survival | cave | adventure | shipwrecked | volcano | porkland
+    pub world_type: Option<String>,
+    /// save file of the last world, eg: survival_1, cave_1_2
+    pub save_file: Option<String>,
+    /// engine crash message, the game exits without a Lua error screen
+    pub crash: Option<String>,
+}
+
+impl DsInfo {
+    fn add_dlc(&mut self, dlc: DsDlc) {
+        if !self.dlcs.contains(&dlc) {
+            self.dlcs.push(dlc);
+        }
+    }
+
+    /// `DLC0001 enabled`, `REIGN_OF_GIANTS is enabled`, ...
+    fn on_dlc_line(&mut self, line: &str) {
+        static DLC_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"\b(DLC000[1-3]|REIGN_OF_GIANTS|CAPY_DLC|PORKLAND_DLC)\b").unwrap()
+        });
+        let lower = line.to_lowercase();
+        if !lower.contains("enabled") || lower.contains("disabled") || lower.contains("not enabled") || lower.contains("false") {
+            return;
+        }
+        for m in DLC_RE.find_iter(line) {
+            if let Some(dlc) = DsDlc::from_token(m.as_str()) {
+                self.add_dlc(dlc);
+            }
+        }
+    }
+
+    /// save files are named by world type and slot, eg: `survival_1`, `cave_1_2` (slot 1, level 2)
+    /// eg: Loading world: survival_1
+    ///     Saving shipwrecked_2
+    fn on_save_line(&mut self, line: &str) {
+        static SAVE_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^(?:Loading world:|Loading file:|Saving|Saved) (?:\S*/)?((survival|cave|adventure|shipwrecked|volcano|porkland)_(\d+)(?:_\d+)?)$").unwrap()
+        });
+        let Some(m) = SAVE_RE.captures(line.trim_end()) else { return; };
+        let world_type = m[2].to_string();
+        match world_type.as_str() {
+            "shipwrecked" | "volcano" => self.add_dlc(DsDlc::Shipwrecked),
+            "porkland" => self.add_dlc(DsDlc::Hamlet),
+            _ => {},
+        }
+        self.save_slot = m[3].parse().ok();
+        self.save_file = Some(m[1].to_string());
+        self.world_type = Some(world_type);
+    }
+}
+
+/// total physical memory below this is flagged, in MB
+const LOW_MEMORY_MB: u32 = 4096;
+/// available physical memory below this is flagged, in MB
+const LOW_AVAILABLE_MEMORY_MB: u32 = 1024;
+
+/// unusual launch info, shown in crash summaries
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemWarning {
+    LowMemory,
+    /// `Mode: 32-bit`, mods and large worlds may run out of address space
+    #[serde(rename = "32_bit")]
+    Bits32,
+    /// no GPU driver, eg: llvmpipe, Microsoft Basic Render Driver
+    SoftwareRendering,
+}
+
+impl SystemWarning {
+    /// same as serialized form, eg: low_memory
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SystemWarning::LowMemory => "low_memory",
+            SystemWarning::Bits32 => "32_bit",
+            SystemWarning::SoftwareRendering => "software_rendering",
+        }
+    }
+}
+
+/// launch and system info of the game process, from lines before the first game instance
+/// eg: Steam Branch: public
+///     \tAvailable Physical Memory: 8541m/16291m
+///     GL_RENDERER: ANGLE (NVIDIA GeForce GTX 1060 Direct3D11 vs_5_0 ps_5_0)
+///     CreateWindow: Requesting 1920,1080 - 5/6/5 - -1/-1/-1 - 0
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct SystemInfo {
+    pub os: Option<String>,
+    pub cpu: Option<String>,
+    /// total physical memory in MB
+    pub memory_mb: Option<u32>,
+    /// available physical memory at launch in MB
+    pub available_memory_mb: Option<u32>,
+    /// GL_VENDOR
+    pub gpu_vendor: Option<String>,
+    /// GL_RENDERER
+    pub renderer: Option<String>,
+    /// GL_VERSION
+    pub gl_version: Option<String>,
+    /// requested window size, eg: 1920x1080
+    pub display: Option<String>,
+    /// own command line arguments, args of started shards are not included
+    pub command_line: Vec<String>,
+    /// eg: public
+    pub steam_branch: Option<String>,
+    pub steam_build_id: Option<String>,
+    /// eg: english
+    pub language: Option<String>,
+    /// filled at eof
+    pub warnings: Vec<SystemWarning>,
+}
+
+impl SystemInfo {
+    /// returns true if the line is launch info
+    fn on_line(&mut self, line: &str) -> bool {
+        static INFO_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(concat!(
+                r"^(?:(?P<os>OS|OS Version|Operating System)|(?P<cpu>CPU|CPU Brand|Processor)): (?P<value>.+)$",
+                r"|^GL_(?P<gl>VENDOR|RENDERER|VERSION): (?P<gl_value>.+)$",
+                r"|^Steam (?P<steam>Branch|Language|AppBuildID): (?P<steam_value>.+)$",
+                r"|^Steam BetaName: \[(?P<beta>[^\]]*)\]",
+                r"|^CreateWindow: Requesting (?P<width>\d+),(?P<height>\d+)",
+                r"|^Available Physical Memory: (?P<available>\d+)(?P<available_unit>[kmg])/(?P<total>\d+)(?P<total_unit>[kmg])",
+            )).unwrap()
+        });
+        let Some(m) = INFO_RE.captures(line) else { return false; };
+        let text = |name: &str| m.name(name).map(|v| v.as_str().trim().to_string()).filter(|v| !v.is_empty());
+        if m.name("os").is_some() {
+            self.os = text("value");
+        }
+        else if m.name("cpu").is_some() {
+            self.cpu = text("value");
+        }
+        else if let Some(gl) = m.name("gl") {
+            let value = text("gl_value");
+            match gl.as_str() {
+                "VENDOR" => self.gpu_vendor = value,
+                "RENDERER" => self.renderer = value,
+                _ => self.gl_version = value,
+            }
+        }
+        else if let Some(steam) = m.name("steam") {
+            let value = text("steam_value");
+            match steam.as_str() {
+                "Branch" => self.steam_branch = value,
+                "Language" => self.language = value,
+                _ => self.steam_build_id = value,
+            }
+        }
+        else if m.name("beta").is_some() {
+            self.steam_branch = Some(text("beta").unwrap_or_else(|| "public".to_string()));
+        }
+        else if m.name("width").is_some() {
+            self.display = Some(format!("{}x{}", &m["width"], &m["height"]));
+        }
+        else {
+            let to_mb = |value: &str, unit: &str| value.parse::<u32>().ok().map(|n| match unit {
+                "k" => n / 1024,
+                "g" => n.saturating_mul(1024),
+                _ => n,
+            });
+            self.available_memory_mb = to_mb(&m["available"], &m["available_unit"]);
+            self.memory_mb = to_mb(&m["total"], &m["total_unit"]);
+        }
+        true
+    }
+
+    fn is_software_renderer(&self) -> bool {
+        let Some(renderer) = self.renderer.as_ref() else { return false; };
+        let renderer = renderer.to_lowercase();
+        ["llvmpipe", "softpipe", "swiftshader", "software", "basic render driver", "gdi generic"].iter()
+            .any(|name| renderer.contains(name))
+    }
+
+    fn check(&mut self, build_arch: &str) {
+        self.warnings.clear();
+        if self.memory_mb.is_some_and(|n| n < LOW_MEMORY_MB)
+            || self.available_memory_mb.is_some_and(|n| n < LOW_AVAILABLE_MEMORY_MB) {
+            self.warnings.push(SystemWarning::LowMemory);
+        }
+        if build_arch == "32-bit" {
+            self.warnings.push(SystemWarning::Bits32);
+        }
+        if self.is_software_renderer() {
+            self.warnings.push(SystemWarning::SoftwareRendering);
+        }
+    }
+}
+
+/// annotation on the important parts of log content
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct LogComment {
+    pub fields: Vec<LogField>,
+    /// if this log contains Lua stacktrace (printed from `StackTraceToLog()`)
+    pub has_stacktrace: bool,
+    /// if this log contains Lua Error
+    pub has_lua_crash: bool,
+    /// if this log contains force crash (eg. Assertion)
+    pub has_c_crash: bool,
+    /// eg: 654321
+    pub build_version: String,
+    /// eg: WIN32_STEAM
+    pub build_platform: String,
+    /// eg: 64-bit
+    pub build_arch: String,
+    /// ds | dst, from `Don't Starve...:` header, empty if not found
+    pub game: String,
+    /// detected when the whole log is parsed
+    pub kind: LogKind,
+    /// DLCs, save slot and crash of DS, None for other games
+    pub ds: Option<DsInfo>,
+    pub system: SystemInfo,
+    /// eg: Mounting file system databundles/klump.zip successful.
+    /// true: using *.zip, false: using files (debug)
+    pub databundles_mounting_state: HashMap<String, bool>,
+    /// registed mod by ModIndex, only in DST log
+    #[serde(skip)]
+    pub mods_registed: HashMap<String, ()>,
+    /// actual mod loaded, in load order
+    /// eg: Loading mod: workshop-727774324 (Craft Pot) Version:0.15.0
+    #[serde(serialize_with = "serialize_mods")]
+    #[ts(as = "Vec<Mod>")]
+    pub mods: IndexMap<String, Mod>,
+    /// mods which were not loaded or have load problems, sorted by moddir, filled at eof
+    pub mod_diagnostics: Vec<ModDiagnostic>,
+    /// mods in load order, with load time and registered prefabs
+    pub mod_profile: Vec<ModLoadProfile>,
+    /// Lua errors in order of appearance
+    pub errors: Vec<LuaError>,
+    /// game instances in order of appearance
+    pub sessions: Vec<Session>,
+    /// total runtime of the log, usually get from the last line
+    pub total_time: Vec<u32>,
+    /// save locations, see `related::find_related_files`
+    pub storage: StorageRefs,
+
+    #[serde(skip)]
+    state: LogState,
+}
+
+impl LogComment {
+    pub fn parse_line_u8(&mut self, mut line: &[u8]) {
+        self.state.current_line += 1;
+        // println!("line: {}", self.state.current_line);
+        if line.len() > MAX_LINE_LEN {
+            return;
+        }
+        for end in [b'\n', b'\r', b'\t'].iter() {
+            if line.ends_with(&[*end]) {
+                line = &line[..line.len() - 1];
+            }
+        }
+        let line = String::from_utf8_lossy(line).to_string();
+        self.parse_line_impl(line.as_str());
+    }
+
+    fn parse_line_impl(&mut self, line: &str) {
+        // strip [00:00:00]
+        static RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^\[(\d+):(\d+):(\d+)\]:\s").unwrap()
+        });
+        // has_time is true if we found time prefix
+        let (has_time, mut line) = match RE.captures(utf8_first(line, 20)) {
+            Some(m)=> {
+                let hour = m.get(1).unwrap().as_str().parse::<u32>().unwrap();
+                let minute = m.get(2).unwrap().as_str().parse::<u32>().unwrap();
+                let second = m.get(3).unwrap().as_str().parse::<u32>().unwrap();
+                self.total_time.copy_from_slice(&[hour, minute, second]);
+                (true, &line[m.get(0).unwrap().as_str().len()..])
+            },
+            None=> {
+                (false, line)
+            },
+        };
+        let last_line = std::mem::replace(&mut self.state.last_line, line.to_string());
+        // collect traceback lines until next line with time prefix
+        if self.state.current_field_name == "lua_crash" {
+            if has_time {
+                self.on_exit_field(self.state.current_line - 1);
+            }
+            else {
+                if !line.is_empty() {
+                    if let Some(error) = self.errors.last_mut() {
+                        error.traceback.push(line.to_string());
+                    }
+                }
+                return;
+            }
+        }
+        if self.build_version.is_empty() && line.starts_with("Don't Starve") {
+            // Don't Starve Together: 654321 WIN32_STEAM
+            // Don't Starve: 578406 OSX_STEAM
+            static RE: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r"^Don't Starve( Together)?: (\d+) ([A-Z0-9_]+)").unwrap()
+            });
+            if let Some(m) = RE.captures(line) {
+                self.game.push_str(if m.get(1).is_some() { "dst" } else { "ds" });
+                self.build_version.push_str(m.get(2).unwrap().as_str());
+                self.build_platform.push_str(m.get(3).unwrap().as_str());
+                return;
+            }
+        }
+
+        if self.build_arch.is_empty() {
+            // Mode: 64-bit
+            static RE: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r"^Mode: ([\w-]+)").unwrap()
+            });
+            if let Some(m) = RE.captures(line) {
+                self.build_arch.push_str(m.get(1).unwrap().as_str());
+                return;
+            }
+        }
+
+        // launch info, memory is printed in an indented block without time prefix
+        if self.sessions.is_empty() && self.system.on_line(line.trim_start()) {
+            return;
+        }
+
+        // Mounting file system databundles/klump.zip successful.
+        static BUNDLE_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^Mounting file system databundles/([\w_]+\.zip) (successful|skipped)\.$").unwrap()
+        });
+        if let Some(m) = BUNDLE_RE.captures(line) {
+            let file = m.get(1).unwrap().as_str().to_string();
+            let is_zip = m.get(2).unwrap().as_str() == "successful";
+            self.databundles_mounting_state.insert(file, is_zip);
+            return;
+        }
+
+        // PersistRootStorage is now APP:Klei//DoNotStarveTogether/123456/
+        if let Some(root) = line.strip_prefix("PersistRootStorage is now ") {
+            let root = root.strip_prefix("APP:").unwrap_or(root);
+            StorageRefs::push_unique(&mut self.storage.persist_roots, root.trim());
+            return;
+        }
+        // Command Line Arguments: -persistent_storage_root ... -cluster Cluster_1 -shard Master
+        // About to start a shard with these parameters: ...
+        for prefix in ["Command Line Arguments:", "About to start a shard with these parameters:"] {
+            if let Some(args) = line.strip_prefix(prefix) {
+                // the client prints args of shards it starts, only our own args tell the kind
+                if prefix == "Command Line Arguments:" && self.sessions.is_empty() {
+                    self.system.command_line = split_command(args);
+                    let has_arg = |name: &str| self.system.command_line.iter().any(|a| a == name);
+                    self.state.server |= has_arg("-cluster") || has_arg("-shard");
+                    self.state.child_shard |= has_arg("-monitor_parent_process");
+                }
+                self.storage.parse_args(args);
+                return;
+            }
+        }
+        // Serializing world: session/3C8B4D1F1F61F9E5/0000000002
+        if line.contains("session/") {
+            static SESSION_RE: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r"\bsession/([0-9A-F]{16})\b").unwrap()
+            });
+            if let Some(m) = SESSION_RE.captures(line) {
+                StorageRefs::push_unique(&mut self.storage.sessions, m.get(1).unwrap().as_str());
+            }
+        }
+
+        if line == "cGame::StartPlaying" {
+            self.state.playing = true;
+            self.on_exit_mod_timer(None);
+            self.on_exit_launching_info();
+            self.on_exit_session(self.state.current_line - 1);
+            self.sessions.push(Session {
+                start: self.state.current_line,
+                end: 0,
+                start_time: self.total_time.clone(),
+            });
+            return;
+        }
+
+        // strip Lua debug print prefix
+        // eg: scripts/widgets/craftslot.lua(99,1) 
+        static LUA_DEBUG_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^scripts/([\w/]+\.lua)\(\d+,\d+\)\s").unwrap()
+        });
+        // let mut lua_src = None;
+        if let Some(m) = LUA_DEBUG_RE.captures(line) {
+            // lua_src = Some(m.get(1).unwrap().as_str());
+            line = &line[m.get(0).unwrap().end()..];
+        }
+        self.on_kind_marker(line);
+        if self.game == "ds" {
+            self.on_crash_line(line);
+            self.state.ds.on_dlc_line(line);
+            self.state.ds.on_save_line(line);
+        }
+
+        /// ModIndex:GetModsToLoad inserting moddir, \tworkshop-2771766820
+        const MODDIR_PREFIX: &str = "ModIndex:GetModsToLoad inserting moddir, \t";
+        if let Some(n) = line.find(MODDIR_PREFIX) {
+            let moddir = &line[n + MODDIR_PREFIX.len()..];
+            self.mods_registed.insert(moddir.to_string(), ());
+            return;
+        }
+        
+        // scripts/mods.lua(179,1)\s?
+        // Loading mod: workshop-351325790 (Geometric Placement) Version:3.2.0	
+        static LOADING_MOD_PREFIX_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^(Fontend\-|Frontend\-)?Loading mod:\s").unwrap()
+        });
+        if let Some(n) = LOADING_MOD_PREFIX_RE.shortest_match(line) {
+            let mut line = &line[n..];
+            // strip final version if we found
+            let version = match line.rfind(" Version:") {
+                Some(n)=> {
+                    let version = &line[n + " Version:".len()..];
+                    line = &line[..n];
+                    Some(version.to_string())
+                },
+                None=> None,
+            };
+            // match workshop
+            static WORKSHOP_RE: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r"(workshop\-\d+)").unwrap()
+            });
+            if let Some(m) = WORKSHOP_RE.captures(line) {
+                let name_index = m.get(0).unwrap().end() + 2;
+                let name = &line[name_index..line.len() - 1];
+                let moddir = m.get(1).unwrap().as_str();
+                let workshop_id = &moddir["workshop-".len()..];
+                self.mods.entry(moddir.to_string()).or_insert(Mod{
+                    moddir: moddir.to_string(),
+                    name: name.to_string(),
+                    version,
+                    workshop_id: Some(workshop_id.to_string()),
+                });
+                self.mod_profile_entry(moddir, name);
+                return;
+            }
+            // match local mods
+            for moddir in self.mods_registed.keys() {
+                if line.starts_with(format!("{} (", moddir).as_str()) {
+                    let name = &line[moddir.len() + 2..line.len() - 1];
+                    self.mods.entry(moddir.to_string()).or_insert(Mod{
+                        moddir: moddir.to_string(),
+                        name: name.to_string(),
+                        version,
+                        workshop_id: None,
+                    });
+                    let moddir = moddir.clone();
+                    self.mod_profile_entry(&moddir, name);
+                    return;
+                }
+            }
+            // DS does not print registered moddirs, and local moddirs may contain spaces
+            // eg: Loading mod: Always On Status (Always On Status) Version:1.2
+            static DS_MOD_RE: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r"^(.+?) \((.*)\)$").unwrap()
+            });
+            if self.game == "ds" {
+                if let Some(m) = DS_MOD_RE.captures(line) {
+                    let (moddir, name) = (m[1].to_string(), m[2].to_string());
+                    self.mods.entry(moddir.clone()).or_insert(Mod{
+                        moddir: moddir.clone(),
+                        name: name.clone(),
+                        version,
+                        workshop_id: None,
+                    });
+                    self.mod_profile_entry(&moddir, &name);
+                    return;
+                }
+            }
+        }
+
+        // Mod: workshop-727774324 (Craft Pot)\tLoading modmain.lua
+        static MOD_PREFIX_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^Mod: (.+?) \((.*?)\)\t(.*)$").unwrap()
+        });
+        if line.starts_with("Mod: ") {
+            if let Some(m) = MOD_PREFIX_RE.captures(line) {
+                self.on_mod_line(&m[1], &m[2], &m[3]);
+                return;
+            }
+        }
+
+        if line == "stack traceback:" {
+            self.has_stacktrace = true;
+        }
+
+        if let Some((kind, moddir)) = mod_problem(line) {
+            let problem = ModProblem { kind, line: self.state.current_line, message: line.to_string() };
+            self.state.mod_problems.push((moddir, problem));
+        }
+
+        // LUA ERROR stack traceback:
+        if line.contains("LUA ERROR stack traceback:") {
+            self.has_lua_crash = true;
+            // error message is printed on the previous line
+            let start = self.state.current_line.saturating_sub(1);
+            self.errors.push(LuaError {
+                line: start,
+                message: last_line,
+                traceback: vec![],
+            });
+            self.state.current_field_name.push_str("lua_crash");
+            self.state.current_field_line = start;
+        }
+    }
+
+    /// close current content block and save it to `fields`
+    fn on_exit_field(&mut self, end: usize) {
+        if self.state.current_field_name.is_empty() {
+            return;
+        }
+        let name = std::mem::take(&mut self.state.current_field_name);
+        let extra = match name.as_str() {
+            "lua_crash" => self.errors.last().map(|e| e.message.clone()).unwrap_or_default(),
+            _ => String::new(),
+        };
+        self.fields.push(LogField {
+            start: self.state.current_field_line,
+            end,
+            kind: name,
+            extra,
+        });
+    }
+
+    fn on_exit_session(&mut self, end: usize) {
+        if let Some(session) = self.sessions.last_mut() {
+            if session.end == 0 {
+                session.end = end;
+            }
+        }
+    }
+
+    /// server, worldgen and playing markers for `LogKind`
+    /// eg: Starting Dedicated Server Game
+    ///     [Shard] Starting master server
+    ///     Generating world with these parameters: ...
+    fn on_kind_marker(&mut self, line: &str) {
+        static SERVER_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^(Starting Dedicated Server Game|\[Shard\] Starting (master server|secondary shard))").unwrap()
+        });
+        static WORLDGEN_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^(Generating world with these parameters:|WorldSim::SimThread::Main\(\)|Creating story\.\.\.|Running worldgen)").unwrap()
+        });
+        static PLAYING_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^(Sim paused|Server registered via geo DNS|\[Shard\] secondary shard .* connected)").unwrap()
+        });
+        if SERVER_RE.is_match(line) {
+            self.state.server = true;
+        }
+        else if WORLDGEN_RE.is_match(line) {
+            self.state.worldgen = true;
+        }
+        else if PLAYING_RE.is_match(line) {
+            self.state.playing = true;
+        }
+    }
+
+    /// engine crash of DS, the game exits without a Lua error screen
+    /// eg: Assert failure 'inst' at ..\source\simlib\entity.cpp(123): Trace follows...
+    fn on_crash_line(&mut self, line: &str) {
+        static CRASH_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^(Assert failure\b|ASSERT FAILED\b|Fatal error\b|Segmentation fault\b|Unhandled exception\b|Force crash\b)").unwrap()
+        });
+        if !CRASH_RE.is_match(line) {
+            return;
+        }
+        self.has_c_crash = true;
+        self.fields.push(LogField {
+            start: self.state.current_line,
+            end: self.state.current_line,
+            kind: "c_crash".to_string(),
+            extra: line.to_string(),
+        });
+        if self.state.ds.crash.is_none() {
+            self.state.ds.crash = Some(line.to_string());
+        }
+    }
+
+    fn detect_kind(&self) -> LogKind {
+        if self.state.worldgen && !self.state.playing {
+            return LogKind::Worldgen;
+        }
+        match self.game.as_str() {
+            "ds" => LogKind::Ds,
+            _ if self.state.server && self.state.child_shard => LogKind::DstServer,
+            _ if self.state.server => LogKind::Dedicated,
+            "dst" => LogKind::DstClient,
+            _ => LogKind::Unknown,
+        }
+    }
+
+    /// game type for mod and script lookup, the header wins over where the file came from
+    pub fn game_type(&self, path: &LogPath) -> String {
+        match self.game.as_str() {
+            "" if self.kind.is_server() => "dst".to_string(),
+            "" => path.get_game_type(),
+            game => game.to_string(),
+        }
+    }
+
+    /// called after the last line is parsed
+    pub fn on_eof(&mut self) {
+        self.on_exit_field(self.state.current_line);
+        self.on_exit_session(self.state.current_line);
+        self.on_exit_mod_timer(None);
+        self.kind = self.detect_kind();
+        self.system.check(&self.build_arch);
+        if self.game == "ds" {
+            self.ds = Some(self.state.ds.clone());
+        }
+        self.build_mod_diagnostics();
+    }
+
+    /// profile of a mod, created in order of first appearance
+    fn mod_profile_entry(&mut self, moddir: &str, name: &str) -> &mut ModLoadProfile {
+        let index = match self.mod_profile.iter().position(|p| p.moddir == moddir) {
+            Some(i) => i,
+            None => {
+                self.mod_profile.push(ModLoadProfile {
+                    moddir: moddir.to_string(),
+                    name: name.to_string(),
+                    order: self.mod_profile.len() + 1,
+                    ..Default::default()
+                });
+                self.mod_profile.len() - 1
+            }
+        };
+        &mut self.mod_profile[index]
+    }
+
+    /// `Mod: <moddir> (<name>)\t<message>`
+    fn on_mod_line(&mut self, moddir: &str, name: &str, message: &str) {
+        let now = self.current_seconds();
+        let phase = if message.starts_with("Loading mod") && message.ends_with(".lua") {
+            Some(ModPhase::Main)
+        }
+        else if message == "Registering prefabs" {
+            Some(ModPhase::Prefabs)
+        }
+        else {
+            None
+        };
+        if let Some(phase) = phase {
+            let next = ModTimer { moddir: moddir.to_string(), phase, start: now, last_seen: now };
+            let same_mod = self.state.mod_timer.as_ref()
+                .is_some_and(|t| t.moddir == moddir && t.phase == phase);
+            // eg: modworldgenmain.lua and modmain.lua of the same mod
+            if !same_mod {
+                self.on_exit_mod_timer(Some(next));
+            }
+        }
+        if let Some(timer) = self.state.mod_timer.as_mut().filter(|t| t.moddir == moddir) {
+            timer.last_seen = now;
+        }
+        let profile = self.mod_profile_entry(moddir, name);
+        let message = message.trim_end();
+        if message.starts_with("  Registering prefab file: ") {
+            profile.prefab_files += 1;
+        }
+        else if message.starts_with("    ") && !message.trim().is_empty() {
+            // prefab names are listed under the prefab file
+            profile.prefabs += 1;
+        }
+    }
+
+    /// stop timing current mod and start `next`
+    /// time of a mod ends when the next mod of the same phase starts, otherwise at its last line,
+    /// so that the game's own loading after the last mod is not counted
+    fn on_exit_mod_timer(&mut self, next: Option<ModTimer>) {
+        if let Some(timer) = std::mem::replace(&mut self.state.mod_timer, next.clone()) {
+            let end = match next {
+                Some(next) if next.phase == timer.phase => next.start,
+                _ => timer.last_seen,
+            };
+            let seconds = end.saturating_sub(timer.start);
+            if let Some(profile) = self.mod_profile.iter_mut().find(|p| p.moddir == timer.moddir) {
+                match timer.phase {
+                    ModPhase::Main => profile.load_seconds += seconds,
+                    ModPhase::Prefabs => profile.prefab_seconds += seconds,
+                }
+            }
+        }
+    }
+
+    /// time prefix of current line in seconds
+    fn current_seconds(&self) -> u32 {
+        match self.total_time.as_slice() {
+            [h, m, s] => h * 3600 + m * 60 + s,
+            _ => 0,
+        }
+    }
+
+    /// compare registered and loaded mods, and group problems by mod
+    fn build_mod_diagnostics(&mut self) {
+        let mut result: Vec<ModDiagnostic> = vec![];
+        for (moddir, problem) in std::mem::take(&mut self.state.mod_problems) {
+            match result.iter_mut().find(|d| d.moddir == moddir) {
+                // the same reason is often printed more than once
+                Some(d) if d.problems.iter().any(|p| p.kind == problem.kind && p.message == problem.message) => {},
+                Some(d) => d.problems.push(problem),
+                None => result.push(ModDiagnostic { moddir, name: None, loaded: false, problems: vec![problem] }),
+            }
+        }
+        // the log may end before mods are loaded
+        let registered = if self.sessions.is_empty() { None } else { Some(self.mods_registed.keys()) };
+        for moddir in registered.into_iter().flatten().filter(|m| !self.mods.contains_key(*m)) {
+            let problem = ModProblem {
+                kind: ModProblemKind::NotLoaded,
+                line: 0,
+                message: format!("{} is registered but not loaded", moddir),
+            };
+            match result.iter_mut().find(|d| d.moddir == *moddir) {
+                Some(d) => d.problems.insert(0, problem),
+                None => result.push(ModDiagnostic { moddir: moddir.clone(), name: None, loaded: false, problems: vec![problem] }),
+            }
+        }
+        for d in result.iter_mut() {
+            if let Some(m) = self.mods.get(&d.moddir) {
+                d.name = Some(m.name.clone());
+                d.loaded = true;
+            }
+            else {
+                // eg: Disabling workshop-123 (Name) because ...
+                let prefix = format!("{} (", d.moddir);
+                d.name = d.problems.iter()
+                    .filter_map(|p| p.message.split_once(prefix.as_str()))
+                    .filter_map(|(_, rest)| rest.split_once(')'))
+                    .map(|(name, _)| name.to_string())
+                    .next();
+            }
+        }
+        result.sort_by(|a, b| a.moddir.cmp(&b.moddir));
+        self.mod_diagnostics = result;
+    }
+
+    pub fn get_mod_name(&self, moddir: &str) -> Option<&str> {
+        self.mods.get(moddir).map(|m| m.name.as_str())
+    }
+
+    /// insert default values after launching info
+    fn on_exit_launching_info(&mut self) {
+        if self.build_version.is_empty() {
+            self.build_version.push_str("unknown");
+        }
+        if self.build_platform.is_empty() {
+            self.build_platform.push_str("unknown");
+        }
+        if self.build_arch.is_empty() {
+            self.build_arch.push_str("unknown");
+        }
+    }
+}
+
+/// kind and moddir of a line which explains why a mod is not loaded
+/// eg: Disabling workshop-123 (Name) because it had an error.
+///     Mod workshop-123 (Name) was built for an older version of the game and requires updating. (api_version is version 6, game is version 10.)
+///     [Workshop] OnDownloadPublishedFileResult for item 123456789 failed with result 2
+fn mod_problem(line: &str) -> Option<(ModProblemKind, String)> {
+    // local moddirs may contain spaces, so every pattern is anchored on both sides of the moddir
+    static PROBLEM_RES: Lazy<Vec<(ModProblemKind, Regex)>> = Lazy::new(|| {
+        [
+            (ModProblemKind::DisabledForCrash, r"^Disabling (?P<moddir>.+?)(?: \(.*\))? because it (?:had an error|crashed)"),
+            (ModProblemKind::MissingDependency, r"^Disabling (?P<moddir>.+?)(?: \(.*\))? because (?:its |of )?(?:missing )?dependenc"),
+            (ModProblemKind::OutdatedApi, r"^Mod (?P<moddir>.+?)(?: \(.*\))? (?:was built for|is for) .*\(api_version is version -?\d+, game is version \d+\.?\)$"),
+            (ModProblemKind::OutOfDate, r"^Mod (?P<moddir>.+?)(?: \(.*\))? is out of date\.?$"),
+            (ModProblemKind::LoadError, r"^Error loading mod: (?P<moddir>.+?)(?: \(.*\))?!"),
+            (ModProblemKind::WorkshopFailure, r"^\[Workshop\] .*\bitem (?P<item>\d+) (?:failed|error|timed out)"),
+        ].into_iter().map(|(kind, re)| (kind, Regex::new(re).unwrap())).collect()
+    });
+    PROBLEM_RES.iter().find_map(|(kind, re)| {
+        let m = re.captures(line)?;
+        let moddir = match m.name("item") {
+            Some(item) => format!("workshop-{}", item.as_str()),
+            None => m["moddir"].to_string(),
+        };
+        Some((*kind, moddir))
+    })
+}
+
+/// Parse the whole log content line by line.
+pub fn parse_log_content(f: impl Read) -> Result<LogComment, LogError> {
+    // TODO: 这里似乎丢失了所有权，导致无法追踪f.tell()
+    let mut reader = LineReader::new(f);
+    let mut comment = LogComment{ 
+        total_time: vec![0, 0, 0],
+        state: LogState::default(),
+        ..Default::default()
+    };
+    read_lines!(line in reader, {
+        match line {
+            Ok(line) => {
+                comment.parse_line_u8(line);
+            },
+            Err(e) => return Err(e.into())
+        }
+    });
+    comment.on_eof();
+    Ok(comment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(log: &str) -> LogComment {
+        parse_log_content(log.as_bytes()).unwrap()
+    }
+
+    const CLIENT_LOG: &str = "\
+[00:00:00]: Don't Starve Together: 654321 WIN32_STEAM
+[00:00:00]: Mode: 64-bit
+[00:00:00]: PersistRootStorage is now APP:Klei//DoNotStarveTogether/123456/
+[00:00:01]: About to start a shard with these parameters: -persistent_storage_root \"C:/My Server\" -conf_dir DoNotStarveTogether -cluster Cluster_1 -shard Caves
+[00:00:02]: cGame::StartPlaying
+[00:00:10]: Serializing world: session/3C8B4D1F1F61F9E5/0000000002
+[00:01:10]: [string \"../mods/workshop-727774324/scripts/widgets/foodcrafting.lua\"]:45: attempt to index local 'recipe' (a nil value)
+LUA ERROR stack traceback:
+../mods/workshop-727774324/scripts/widgets/foodcrafting.lua:45 in (method) UpdateRecipes (Lua) <40-60>
+   self =
+      recipe = nil
+scripts/widgets/widget.lua:120 in (method) OnUpdate (Lua) <110-130>
+[00:01:11]: cGame::StartPlaying
+[00:02:00]: Shutting down
+";
+
+    #[test]
+    fn lua_errors() {
+        let comment = parse(CLIENT_LOG);
+        assert!(comment.has_lua_crash);
+        assert_eq!(comment.errors.len(), 1);
+        let error = &comment.errors[0];
+        assert_eq!(error.line, 7);
+        assert!(error.message.ends_with("attempt to index local 'recipe' (a nil value)"));
+        assert_eq!(error.traceback.len(), 4);
+        assert_eq!(error.frames().collect::<Vec<_>>(), vec![
+            "../mods/workshop-727774324/scripts/widgets/foodcrafting.lua:45 in (method) UpdateRecipes (Lua) <40-60>",
+            "scripts/widgets/widget.lua:120 in (method) OnUpdate (Lua) <110-130>",
+        ]);
+        assert_eq!(error.suspected_moddir().as_deref(), Some("workshop-727774324"));
+    }
+
+    #[test]
+    fn sessions() {
+        let comment = parse(CLIENT_LOG);
+        let sessions = comment.sessions.iter().map(|s| (s.start, s.end, s.start_time.clone())).collect::<Vec<_>>();
+        assert_eq!(sessions, vec![(5, 12, vec![0, 0, 2]), (13, 14, vec![0, 1, 11])]);
+        assert_eq!(comment.total_time, vec![0, 2, 0]);
+    }
+
+    #[test]
+    fn storage_refs() {
+        let storage = parse(CLIENT_LOG).storage;
+        assert_eq!(storage.persist_roots, vec!["Klei//DoNotStarveTogether/123456/"]);
+        assert_eq!(storage.storage_root.as_deref(), Some("C:/My Server"));
+        assert_eq!(storage.conf_dir.as_deref(), Some("DoNotStarveTogether"));
+        assert_eq!(storage.cluster.as_deref(), Some("Cluster_1"));
+        assert_eq!(storage.shards, vec!["Caves"]);
+        assert_eq!(storage.sessions, vec!["3C8B4D1F1F61F9E5"]);
+    }
+
+    #[test]
+    fn mods_keep_load_order() {
+        let comment = parse("\
+[00:00:02]: ModIndex:GetModsToLoad inserting moddir, \tzz_local
+[00:00:02]: ModIndex:GetModsToLoad inserting moddir, \tworkshop-9
+[00:00:02]: ModIndex:GetModsToLoad inserting moddir, \tworkshop-10
+[00:00:02]: Loading mod: zz_local (Zebra) Version:1.0
+[00:00:02]: Loading mod: workshop-9 (Beta) Version:2.0
+[00:00:03]: Loading mod: workshop-10 (Alpha)
+[00:00:03]: Mod: zz_local (Zebra)\tLoading modmain.lua
+");
+        let order = ["zz_local", "workshop-9", "workshop-10"];
+        assert_eq!(comment.mods.keys().collect::<Vec<_>>(), order);
+        assert_eq!(comment.mod_profile.iter().map(|p| p.moddir.as_str()).collect::<Vec<_>>(), order);
+        let value = serde_json::to_value(&comment).unwrap();
+        let moddirs = value["mods"].as_array().unwrap().iter()
+            .map(|m| m["moddir"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(moddirs, order);
+        assert_eq!(comment.mods["workshop-10"].version, None);
+    }
+
+    #[test]
+    fn mod_load_profile() {
+        let comment = parse("\
+[00:00:02]: Loading mod: workshop-1 (A) Version:1
+[00:00:02]: Loading mod: workshop-2 (B)
+[00:00:03]: Mod: workshop-1 (A)\tLoading modworldgenmain.lua
+[00:00:04]: Mod: workshop-1 (A)\tLoading modmain.lua
+[00:00:10]: Mod: workshop-2 (B)\tLoading modmain.lua
+[00:00:13]: Mod: workshop-2 (B)\t  print from modmain
+[00:00:20]: Mod: workshop-1 (A)\tRegistering prefabs
+[00:00:20]: Mod: workshop-1 (A)\t  Registering prefab file: prefabs/a
+[00:00:21]: Mod: workshop-1 (A)\t    a_item
+[00:00:21]: Mod: workshop-1 (A)\t    a_item_placer
+[00:00:25]: Mod: workshop-2 (B)\tRegistering prefabs
+[00:00:26]: Mod: workshop-2 (B)\t  Registering prefab file: prefabs/b
+[00:00:30]: cGame::StartPlaying
+");
+        let profile = comment.mod_profile.iter()
+            .map(|p| (p.moddir.as_str(), p.order, p.load_seconds, p.prefab_seconds, p.prefab_files, p.prefabs))
+            .collect::<Vec<_>>();
+        // main ends when the next mod starts, or at the last line of the mod before another phase
+        assert_eq!(profile, vec![
+            ("workshop-1", 1, 7, 5, 1, 2),
+            ("workshop-2", 2, 3, 1, 1, 0),
+        ]);
+    }
+
+    #[test]
+    fn detect_kind() {
+        let kind = |log: &str| parse(log).kind;
+        assert_eq!(kind("[00:00:00]: Don't Starve: 578406 OSX_STEAM\n[00:00:01]: cGame::StartPlaying\n"), LogKind::Ds);
+        assert_eq!(kind("[00:00:00]: Don't Starve Together: 654321 WIN32_STEAM\n[00:00:01]: cGame::StartPlaying\n"), LogKind::DstClient);
+        assert_eq!(kind("\
+[00:00:00]: Don't Starve Together: 654321 LINUX_STEAM
+[00:00:00]: Command Line Arguments: -persistent_storage_root /x -cluster Cluster_1 -shard Master
+[00:00:02]: Starting Dedicated Server Game
+[00:00:03]: Sim paused
+"), LogKind::Dedicated);
+        assert_eq!(kind("\
+[00:00:00]: Command Line Arguments: -monitor_parent_process 123 -cluster Cluster_1 -shard Caves
+[00:00:02]: Starting Dedicated Server Game
+[00:00:03]: Sim paused
+"), LogKind::DstServer);
+        assert_eq!(kind("\
+[00:00:00]: Command Line Arguments: -cluster Cluster_1 -shard Master
+[00:00:03]: Generating world with these parameters: ...
+"), LogKind::Worldgen);
+        // args of shards started by the client do not make it a server
+        assert_eq!(kind("\
+[00:00:00]: Don't Starve Together: 654321 WIN32_STEAM
+[00:00:01]: cGame::StartPlaying
+[00:00:02]: Command Line Arguments: -cluster Cluster_1 -shard Master
+"), LogKind::DstClient);
+        assert_eq!(kind("hello\n"), LogKind::Unknown);
+
+        // the header wins over where the file came from
+        let path = LogPath::external("/tmp/server_log.txt");
+        assert_eq!(parse("[00:00:00]: Don't Starve: 578406 OSX_STEAM\n").game_type(&path), "ds");
+        assert_eq!(parse("[00:00:02]: Starting Dedicated Server Game\n").game_type(&path), "dst");
+    }
+
+    #[test]
+    fn ds_info() {
+        let comment = parse(r"[00:00:00]: Starting Up
+[00:00:00]: Don't Starve: 578406 WIN32_STEAM
+[00:00:00]: DLC0001 enabled
+[00:00:02]: Loading mod: Always On Status (Always On Status) Version:1.2
+[00:00:03]: Loading world: porkland_3
+[00:00:04]: cGame::StartPlaying
+[00:00:10]: Recipe for survival_1 is loaded from the mod's save table
+[00:01:04]: Saving shipwrecked_2
+[00:01:05]: [string scripts/mods.lua]: survival_4 serialize failed
+[00:02:00]: Assert failure 'inst' at ..\source\simlib\entity.cpp(123): Trace follows...
+");
+        let ds = comment.ds.as_ref().unwrap();
+        assert_eq!(ds.dlcs, vec![DsDlc::ReignOfGiants, DsDlc::Hamlet, DsDlc::Shipwrecked]);
+        assert_eq!(ds.save_file.as_deref(), Some("shipwrecked_2"));
+        assert_eq!(ds.world_type.as_deref(), Some("shipwrecked"));
+        assert_eq!(ds.save_slot, Some(2));
+        assert!(ds.crash.as_deref().unwrap().starts_with("Assert failure 'inst'"));
+        assert!(comment.has_c_crash);
+        assert_eq!(comment.kind, LogKind::Ds);
+    }
+
+    #[test]
+    fn system_info() {
+        let comment = parse("\
+[00:00:00]: System Memory:
+\tMemory Load: 91%
+\tAvailable Physical Memory: 412m/3071m
+[00:00:00]: Don't Starve Together: 654321 LINUX_STEAM
+[00:00:00]: Mode: 32-bit
+[00:00:00]: Command Line Arguments: -windowed
+[00:00:00]: os: unable to stat file
+[00:00:00]: OS: Ubuntu 22.04
+[00:00:00]: CPU: Intel(R) Celeron(R) N4000
+[00:00:00]: cpu : usage 90%
+[00:00:00]: Steam BetaName: [updatebeta]
+[00:00:00]: CreateWindow: Requesting 1366,768 - 5/6/5 - -1/-1/-1 - 0
+[00:00:00]: GL_VENDOR: Mesa/X.org
+[00:00:00]: GL_RENDERER: llvmpipe (LLVM 15.0.7, 256 bits)
+[00:00:01]: cGame::StartPlaying
+[00:00:02]: OS: not launch info
+");
+        let system = &comment.system;
+        assert_eq!(system.os.as_deref(), Some("Ubuntu 22.04"));
+        assert_eq!(system.cpu.as_deref(), Some("Intel(R) Celeron(R) N4000"));
+        assert_eq!(system.memory_mb, Some(3071));
+        assert_eq!(system.available_memory_mb, Some(412));
+        assert_eq!(system.display.as_deref(), Some("1366x768"));
+        assert_eq!(system.steam_branch.as_deref(), Some("updatebeta"));
+        assert_eq!(system.command_line, vec!["-windowed"]);
+        assert_eq!(system.warnings, vec![SystemWarning::LowMemory, SystemWarning::Bits32, SystemWarning::SoftwareRendering]);
+    }
+
+    #[test]
+    fn engine_crash_is_ds_only() {
+        let comment = parse("\
+[00:00:00]: Don't Starve Together: 612345 LINUX
+[00:00:01]: Assert failure 'inst' at entity.cpp(123): Trace follows...
+[00:00:02]: Saving survival_1
+");
+        assert!(comment.ds.is_none());
+        assert!(!comment.has_c_crash);
+        assert!(comment.fields.iter().all(|f| f.kind != "c_crash"));
+    }
+
+    #[test]
+    fn mod_problems_use_engine_messages() {
+        let comment = parse("\
+[00:00:00]: Don't Starve Together: 612345 LINUX
+[00:00:01]: ModIndex:GetModsToLoad inserting moddir, \tworkshop-111111111
+[00:00:01]: ModIndex:GetModsToLoad inserting moddir, \tworkshop-222222222
+[00:00:01]: ModIndex:GetModsToLoad inserting moddir, \tmy_local_mod
+[00:00:01]: ModIndex:GetModsToLoad inserting moddir, \tworkshop-333333333
+[00:00:02]: [Workshop] OnDownloadPublishedFileResult for item 222222222 failed with result 2
+[00:00:02]: [Workshop] OnDownloadPublishedFileResult for item 222222222 failed with result 2
+[00:00:02]: Mod workshop-333333333 (Old Mod) was built for an older version of the game and requires updating. (api_version is version 6, game is version 10.)
+[00:00:03]: Loading mod: workshop-111111111 (Good Mod) Version:1.0
+[00:00:03]: Disabling my_local_mod (Local) because it had an error.
+[00:00:03]: Mod workshop-111111111 is out of date
+[00:00:03]: my_local_mod crashed while my_local_mod_extra was disabling bad prefabs
+[00:00:03]: workshop-111111111: recipe api_version missing dependency, out-of-date
+[00:00:04]: cGame::StartPlaying
+");
+        let problems = comment.mod_diagnostics.iter()
+            .map(|d| (d.moddir.as_str(), d.name.as_deref(), d.loaded, d.problems.iter().map(|p| p.kind).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(problems, vec![
+            ("my_local_mod", Some("Local"), false, vec![ModProblemKind::NotLoaded, ModProblemKind::DisabledForCrash]),
+            ("workshop-111111111", Some("Good Mod"), true, vec![ModProblemKind::OutOfDate]),
+            ("workshop-222222222", None, false, vec![ModProblemKind::NotLoaded, ModProblemKind::WorkshopFailure]),
+            ("workshop-333333333", Some("Old Mod"), false, vec![ModProblemKind::NotLoaded, ModProblemKind::OutdatedApi]),
+        ]);
+    }
+
+    #[test]
+    fn mod_problem_moddir_comes_from_the_message() {
+        assert_eq!(mod_problem("Disabling My Mod (Fancy) because it had an error."),
+            Some((ModProblemKind::DisabledForCrash, "My Mod".to_string())));
+        assert_eq!(mod_problem("Error loading mod: workshop-1 (A (B))!"),
+            Some((ModProblemKind::LoadError, "workshop-1".to_string())));
+        assert_eq!(mod_problem("Mod workshop-1 crashed"), None);
+        assert_eq!(mod_problem("[Workshop] item 123456789 downloaded"), None);
+    }
+}
@@ -0,0 +1,339 @@
+// Log file location, eg: a local file or an entry of zip archive
+// `LogPath` is a cheap handle of any `LogSource`, built-in sources for local files are defined here
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::archive::ArchiveEntry;
+use crate::error::LogError;
+use crate::ipc::LogInfo;
+use crate::memory::MemorySource;
+use crate::reader::{LogContent, LogData, LogReader, ZipIndex};
+use crate::remote::UrlSource;
+use crate::source::{self, calc_v5, strip_home, Deserializer, LogSource, SourceMeta};
+
+#[derive(Clone)]
+pub struct LogPath(Arc<dyn LogSource>);
+
+impl Default for LogPath {
+    fn default() -> Self {
+        LogPath::external("")
+    }
+}
+
+impl fmt::Debug for LogPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq for LogPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_label() == other.to_label()
+    }
+}
+
+impl LogPath {
+    pub fn new(source: impl LogSource + 'static) -> Self {
+        LogPath(Arc::new(source))
+    }
+
+    /// DS log, eg: `Klei/DoNotStarve/log.txt`
+    pub fn ds(path: impl Into<PathBuf>) -> Self {
+        Self::new(LocalFile { kind: LocalKind::Ds, path: path.into() })
+    }
+
+    /// DST log in local dir, eg: `Klei/DoNotStarveTogether/client_log.txt`
+    pub fn dst_local(path: impl Into<PathBuf>) -> Self {
+        Self::new(LocalFile { kind: LocalKind::DstLocal, path: path.into() })
+    }
+
+    /// DST log in zip of cloud save, eg: `Master.zip:server_log.txt`
+    pub fn dst_cloud(path: impl Into<PathBuf>, name: impl Into<String>) -> Self {
+        Self::new(CloudEntry { path: path.into(), name: name.into() })
+    }
+
+    /// file opened by user
+    pub fn external(path: impl Into<PathBuf>) -> Self {
+        Self::new(LocalFile { kind: LocalKind::External, path: path.into() })
+    }
+
+    /// entry of user provided archive, nested archives come first in the name chain
+    pub fn archive(path: impl Into<PathBuf>, entries: Vec<String>) -> Self {
+        Self::new(ArchiveEntry { path: path.into(), entries })
+    }
+
+    /// pasted text, `name` is generated if not provided
+    pub fn memory(name: Option<String>, content: impl Into<Vec<u8>>) -> Self {
+        Self::new(MemorySource::new(name, content))
+    }
+
+    /// remote log over HTTP(S)
+    pub fn url(url: impl Into<String>) -> Self {
+        Self::new(UrlSource::new(url))
+    }
+
+    pub fn source(&self) -> &dyn LogSource {
+        self.0.as_ref()
+    }
+
+    pub fn get_game_type(&self) -> String {
+        self.0.game_type().to_string()
+    }
+
+    #[inline]
+    pub fn is_zip(&self) -> bool {
+        self.0.is_zip()
+    }
+
+    /// file on local disk, None for remote sources
+    pub fn local_path(&self) -> Option<&Path> {
+        self.0.local_path()
+    }
+
+    pub fn get_name(&self) -> String {
+        self.0.name()
+    }
+
+    /// convert to tauri window label
+    pub fn to_label(&self) -> String {
+        self.0.label()
+    }
+
+    pub fn metadata(&self) -> SourceMeta {
+        self.0.metadata()
+    }
+
+    pub fn to_info(&self) -> LogInfo {
+        let meta = self.metadata();
+        LogInfo {
+            game: self.get_game_type(),
+            filename: self.get_name(),
+            // TODO: 这里是否会导致信息损失？
+            filepath: match self.local_path() {
+                Some(p) => p.to_string_lossy().to_string(),
+                None => self.0.location(),
+            },
+            mtime: meta.mtime,
+            filesize: meta.size,
+            is_zip: self.is_zip(),
+            source: self.serialize(),
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut v = self.0.to_json();
+        v["type"] = self.0.kind().into();
+        v.dump()
+    }
+
+    pub fn deserialize(s: &str) -> Result<LogPath, String> {
+        let v = json::parse(s).map_err(|e| e.to_string())?;
+        source::deserialize(&v)
+    }
+
+    pub fn get_menu_path(&self) -> String {
+        self.0.location()
+    }
+
+    #[inline]
+    pub fn exists(&self) -> bool {
+        self.0.exists()
+    }
+
+    pub fn open(&self) -> Result<LogReader, LogError> {
+        self.0.open()
+    }
+
+    /// read the whole content, large local files are memory-mapped
+    pub fn read_content(&self) -> Result<LogContent, LogError> {
+        self.open()?.into_content()
+            .map_err(|e| LogError::Io(format!("{}: {}", self.get_menu_path(), e)))
+    }
+
+    /// content to keep for range reading, large live logs are not buffered
+    pub fn read_data(&self) -> Result<LogData, LogError> {
+        self.open()?.into_data()
+            .map_err(|e| LogError::Io(format!("{}: {}", self.get_menu_path(), e)))
+    }
+
+    /// text in `start..end` extended to the end of line, read without loading the whole log
+    pub fn read_line_range(&self, start: u64, end: u64) -> Result<(u64, u64, Vec<u8>), LogError> {
+        self.open()?.read_line_range(start, end)
+            .map_err(|e| LogError::Io(format!("{}: {}", self.get_menu_path(), e)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LocalKind {
+    Ds,
+    DstLocal,
+    External,
+}
+
+/// plain log file on local disk
+#[derive(Debug, Clone)]
+struct LocalFile {
+    kind: LocalKind,
+    path: PathBuf,
+}
+
+impl LogSource for LocalFile {
+    fn kind(&self) -> &'static str {
+        match self.kind {
+            LocalKind::Ds => "ds",
+            LocalKind::DstLocal => "dst",
+            LocalKind::External => "external",
+        }
+    }
+
+    fn label(&self) -> String {
+        let prefix = if self.kind == LocalKind::Ds { "ds" } else { "dst" };
+        format!("{}-{}", prefix, calc_v5(&self.path.to_string_lossy()))
+    }
+
+    fn name(&self) -> String {
+        self.path.file_name_utf8()
+    }
+
+    fn location(&self) -> String {
+        strip_home(self.path.to_string_lossy().to_string())
+    }
+
+    fn game_type(&self) -> &'static str {
+        match self.kind {
+            LocalKind::Ds => "ds",
+            LocalKind::DstLocal => "dst",
+            LocalKind::External => "dyn",
+        }
+    }
+
+    fn local_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    fn open(&self) -> Result<LogReader, LogError> {
+        LogReader::open_file(&self.path)
+    }
+
+    fn metadata(&self) -> SourceMeta {
+        self.path.source_meta()
+    }
+
+    fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            "path": self.path.to_string_lossy().to_string(),
+        }
+    }
+}
+
+/// entry of cloud save zip
+#[derive(Debug, Clone)]
+struct CloudEntry {
+    path: PathBuf,
+    name: String,
+}
+
+impl LogSource for CloudEntry {
+    fn kind(&self) -> &'static str {
+        "dstcloud"
+    }
+
+    fn label(&self) -> String {
+        format!("dstcloud-{}-{}", calc_v5(&self.path.to_string_lossy()), calc_v5(&self.name))
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self) -> String {
+        strip_home(format!("{}:{}", self.path.to_string_lossy(), self.name))
+    }
+
+    fn game_type(&self) -> &'static str {
+        "dst"
+    }
+
+    fn local_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    fn is_zip(&self) -> bool {
+        true
+    }
+
+    fn open(&self) -> Result<LogReader, LogError> {
+        ZipIndex::open(&self.path)?.open_by_name(&self.name)
+    }
+
+    fn metadata(&self) -> SourceMeta {
+        self.path.source_meta()
+    }
+
+    fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            "path": self.path.to_string_lossy().to_string(),
+            "name": self.name.clone(),
+        }
+    }
+}
+
+pub(crate) fn register_builtin(map: &mut HashMap<&'static str, Deserializer>) {
+    fn get_path(v: &json::JsonValue) -> Result<PathBuf, String> {
+        Ok(PathBuf::from(v["path"].as_str().ok_or("path not found")?))
+    }
+    map.insert("ds", |v| Ok(LogPath::ds(get_path(v)?)));
+    map.insert("dst", |v| Ok(LogPath::dst_local(get_path(v)?)));
+    map.insert("external", |v| Ok(LogPath::external(get_path(v)?)));
+    map.insert("dstcloud", |v| {
+        let name = v["name"].as_str().ok_or("name not found")?;
+        Ok(LogPath::dst_cloud(get_path(v)?, name))
+    });
+}
+
+pub trait PathExt {
+    fn file_name_utf8(&self) -> String;
+    fn mtime_f64(&self) -> f64;
+    fn file_size(&self) -> u64;
+    fn source_meta(&self) -> SourceMeta;
+}
+
+impl PathExt for Path {
+    fn file_name_utf8(&self) -> String {
+        self.file_name().unwrap_or_default().to_string_lossy().to_string()
+    }
+
+    fn mtime_f64(&self) -> f64 {
+        match self.metadata() {
+            Ok(meta) => {
+                match meta.modified() {
+                    Ok(time) => {
+                        time.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs_f64()
+                    }
+                    Err(_) => -1.0,
+                }
+            }
+            Err(_) => -1.0,
+        }
+    }
+
+    fn file_size(&self) -> u64 {
+        match self.metadata() {
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        }
+    }
+
+    fn source_meta(&self) -> SourceMeta {
+        if !self.is_file() {
+            return SourceMeta::missing();
+        }
+        SourceMeta {
+            exists: true,
+            mtime: self.mtime_f64(),
+            size: self.file_size(),
+        }
+    }
+}
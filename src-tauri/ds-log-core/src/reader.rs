@@ -0,0 +1,384 @@
+// Unified reader for plain log files, entries of zip archive (cloud saves) and in-memory content
+use std::borrow::Cow;
+use std::fs::{File, Metadata};
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use memmap2::Mmap;
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::error::LogError;
+use crate::inflate::InflateReader;
+
+/// Parsed zip archive, cheap to clone and share between threads.
+/// Every opened entry owns its data source, so any number of readers
+/// (eg: Master and Caves logs of one cloud save) can be used at the same time.
+#[derive(Clone)]
+pub struct ZipIndex {
+    path: PathBuf,
+    archive: Arc<Mutex<ZipArchive<File>>>,
+}
+
+impl ZipIndex {
+    pub fn open(path: &Path) -> Result<Self, LogError> {
+        let file = File::open(path).map_err(|e| LogError::io(path, e))?;
+        let archive = ZipArchive::new(file).map_err(|e| LogError::zip(path, None, e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            archive: Arc::new(Mutex::new(archive)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.archive.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn index_for_name(&self, name: &str) -> Option<usize> {
+        self.archive.lock().unwrap().index_for_name(name)
+    }
+
+    pub fn name_for_index(&self, index: usize) -> Option<String> {
+        self.archive.lock().unwrap().name_for_index(index).map(|s| s.to_string())
+    }
+
+    pub fn open_by_name(&self, name: &str) -> Result<LogReader, LogError> {
+        let index = self.index_for_name(name)
+            .ok_or_else(|| LogError::MissingEntry(format!("{}:{}", self.path.display(), name)))?;
+        self.open_by_index(index)
+    }
+
+    pub fn open_by_index(&self, index: usize) -> Result<LogReader, LogError> {
+        let mut archive = self.archive.lock().unwrap();
+        let name = archive.name_for_index(index).map(|s| s.to_string());
+        let zip_error = |e| LogError::zip(&self.path, name.as_deref(), e);
+        let mut entry = archive.by_index(index).map_err(zip_error)?;
+        match entry.compression() {
+            CompressionMethod::Stored => {
+                // read from the archive file directly with a new handle
+                let file = File::open(&self.path).map_err(|e| LogError::io(&self.path, e))?;
+                Ok(LogReader::ForStoredEntry(EntryRange::new(file, entry.data_start(), entry.size())?))
+            },
+            CompressionMethod::Deflated => {
+                // inflate on demand, seeking is sped up by checkpoints
+                let file = File::open(&self.path).map_err(|e| LogError::io(&self.path, e))?;
+                let data = EntryRange::new(file, entry.data_start(), entry.compressed_size())?;
+                let name = name.unwrap_or_default();
+                Ok(LogReader::ForDeflatedEntry(InflateReader::new(data, entry.size(), &self.path, &name, entry.crc32())))
+            },
+            _ => {
+                // other methods are rare, decompress into memory
+                let mut buf = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut buf).map_err(|e| LogError::io(&self.path, e))?;
+                Ok(LogReader::ForBuffer(Cursor::new(buf)))
+            },
+        }
+    }
+}
+
+/// a byte range of file, used for uncompressed zip entry
+pub struct EntryRange {
+    file: File,
+    start: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl EntryRange {
+    pub(crate) fn new(mut file: File, start: u64, len: u64) -> std::io::Result<Self> {
+        file.seek(SeekFrom::Start(start))?;
+        Ok(Self { file, start, len, pos: 0 })
+    }
+
+    pub fn size(&self) -> u64 {
+        self.len
+    }
+}
+
+impl Read for EntryRange {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remain = self.len.saturating_sub(self.pos);
+        let n = buf.len().min(remain as usize);
+        if n == 0 {
+            return Ok(0);
+        }
+        let n = self.file.read(&mut buf[..n])?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for EntryRange {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => self.len.checked_add_signed(n),
+            SeekFrom::Current(n) => self.pos.checked_add_signed(n),
+        };
+        let pos = pos.ok_or_else(|| std::io::Error::new(
+            std::io::ErrorKind::InvalidInput, "invalid seek to a negative position"))?;
+        self.file.seek(SeekFrom::Start(self.start + pos))?;
+        self.pos = pos;
+        Ok(pos)
+    }
+}
+
+pub enum LogReader {
+    ForFile(File),
+    /// large local file, see `LogReader::open_file`
+    ForMmap(Cursor<Mmap>),
+    /// stored (uncompressed) zip entry
+    ForStoredEntry(EntryRange),
+    /// deflated zip entry, eg: logs in cloud saves
+    ForDeflatedEntry(InflateReader),
+    /// decompressed or downloaded content
+    ForBuffer(Cursor<Vec<u8>>),
+    /// content shared with its source, eg: pasted text
+    ForShared(Cursor<Arc<[u8]>>),
+}
+
+/// Files larger than this are memory-mapped instead of read into memory.
+pub const MMAP_THRESHOLD: u64 = 4 * 1024 * 1024;
+
+/// logs written by a running game, truncated on the next launch
+const LIVE_LOG_NAMES: [&str; 4] = ["client_log.txt", "server_log.txt", "server_chat_log.txt", "log.txt"];
+
+/// files modified in this period may still be written, eg: a renamed live log
+const LIVE_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+/// a log which the game may truncate or rewrite, reading a truncated mapping raises SIGBUS,
+/// and on Windows a mapping stops the game from truncating its own log
+fn is_live_log(path: &Path, meta: &Metadata) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+    LIVE_LOG_NAMES.contains(&name.as_str())
+        || meta.modified().ok()
+            .and_then(|t| SystemTime::now().duration_since(t).ok())
+            .is_none_or(|age| age < LIVE_PERIOD)
+}
+
+impl LogReader {
+    /// open a local file, map it into memory if it is large and not written by the game,
+    /// eg: rotated backups and logs of closed sessions
+    pub fn open_file(path: &Path) -> Result<Self, LogError> {
+        let file = File::open(path).map_err(|e| LogError::io(path, e))?;
+        let Ok(meta) = file.metadata() else {
+            return Ok(LogReader::ForFile(file));
+        };
+        if meta.len() >= MMAP_THRESHOLD && !is_live_log(path, &meta) {
+            // fall back to normal reading if mapping is not supported, eg: network drive
+            if let Ok(mmap) = unsafe { Mmap::map(&file) } {
+                return Ok(LogReader::ForMmap(Cursor::new(mmap)));
+            }
+        }
+        Ok(LogReader::ForFile(file))
+    }
+
+    /// total size, without reading a compressed entry to the end
+    pub fn size(&self) -> std::io::Result<u64> {
+        Ok(match self {
+            LogReader::ForFile(f) => f.metadata()?.len(),
+            LogReader::ForMmap(f) => f.get_ref().len() as u64,
+            LogReader::ForStoredEntry(f) => f.size(),
+            LogReader::ForDeflatedEntry(f) => f.size(),
+            LogReader::ForBuffer(f) => f.get_ref().len() as u64,
+            LogReader::ForShared(f) => f.get_ref().len() as u64,
+        })
+    }
+
+    /// keep mapped and small logs in memory, large live logs and zip entries are left as a stream,
+    /// a copy of a live log would be stale on the next write anyway
+    pub fn into_data(self) -> std::io::Result<LogData> {
+        let streamed = matches!(self, LogReader::ForFile(_) | LogReader::ForStoredEntry(_) | LogReader::ForDeflatedEntry(_));
+        if streamed && self.size()? >= MMAP_THRESHOLD {
+            return Ok(LogData::Stream(self));
+        }
+        self.into_content().map(LogData::Content)
+    }
+
+    /// bytes in `start..end` read by seeking, extended to the end of line like `LogContent::line_range`
+    pub fn read_line_range(&mut self, start: u64, end: u64) -> std::io::Result<(u64, u64, Vec<u8>)> {
+        let end = end.min(self.size()?);
+        let start = start.min(end);
+        self.seek(SeekFrom::Start(start))?;
+        let mut buf = vec![];
+        self.take(end - start).read_to_end(&mut buf)?;
+        if end > start {
+            BufReader::new(self).read_until(b'\n', &mut buf)?;
+        }
+        Ok((start, start + buf.len() as u64, buf))
+    }
+
+    /// take the whole content, without copying if it is already in memory
+    pub fn into_content(self) -> std::io::Result<LogContent> {
+        match self {
+            LogReader::ForMmap(c) => Ok(LogContent::Mapped(c.into_inner())),
+            LogReader::ForBuffer(c) => Ok(LogContent::Owned(c.into_inner())),
+            LogReader::ForShared(c) => Ok(LogContent::Shared(c.into_inner())),
+            mut reader => {
+                let mut buf = vec![];
+                reader.read_to_end(&mut buf)?;
+                Ok(LogContent::Owned(buf))
+            },
+        }
+    }
+}
+
+/// content of a log kept for range reading, see `LogReader::into_data`
+pub enum LogData {
+    Content(LogContent),
+    /// large log which can't be mapped, read it by `LogReader::read_line_range`,
+    /// a deflated entry is inflated from the nearest checkpoint
+    Stream(LogReader),
+}
+
+/// whole content of a log, shared by parser, search and range reading
+pub enum LogContent {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+    Shared(Arc<[u8]>),
+}
+
+impl Default for LogContent {
+    fn default() -> Self {
+        LogContent::Owned(vec![])
+    }
+}
+
+impl Deref for LogContent {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            LogContent::Mapped(m) => m,
+            LogContent::Owned(v) => v,
+            LogContent::Shared(v) => v,
+        }
+    }
+}
+
+impl LogContent {
+    /// text of the log, borrowed if it is valid UTF-8
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self)
+    }
+
+    /// bytes in `start..end`, clamped to the content
+    pub fn range(&self, start: usize, end: usize) -> &[u8] {
+        let end = end.min(self.len());
+        &self[start.min(end)..end]
+    }
+
+    /// `start..end` extended to the end of line, so a multi-byte character is never split
+    pub fn line_range(&self, start: usize, end: usize) -> (usize, usize) {
+        let end = end.min(self.len());
+        let start = start.min(end);
+        let end = match self[end..].iter().position(|b| *b == b'\n') {
+            Some(n) if end > start => end + n + 1,
+            _ if end > start => self.len(),
+            _ => end,
+        };
+        (start, end)
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        matches!(self, LogContent::Mapped(_))
+    }
+}
+
+impl Read for LogReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            LogReader::ForFile(f) => f.read(buf),
+            LogReader::ForMmap(f) => f.read(buf),
+            LogReader::ForStoredEntry(f) => f.read(buf),
+            LogReader::ForDeflatedEntry(f) => f.read(buf),
+            LogReader::ForBuffer(f) => f.read(buf),
+            LogReader::ForShared(f) => f.read(buf),
+        }
+    }
+}
+
+impl Seek for LogReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            LogReader::ForFile(f) => f.seek(pos),
+            LogReader::ForMmap(f) => f.seek(pos),
+            LogReader::ForStoredEntry(f) => f.seek(pos),
+            LogReader::ForDeflatedEntry(f) => f.seek(pos),
+            LogReader::ForBuffer(f) => f.seek(pos),
+            LogReader::ForShared(f) => f.seek(pos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ds-log-reader-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn line_range_ends_at_line_end() {
+        let content = LogContent::Owned("[00:00:01]: a\n[00:00:02]: 中文\n[00:00:03]: c".as_bytes().to_vec());
+        // the range ends inside `中`
+        let (start, end) = content.line_range(0, 28);
+        assert_eq!(start, 0);
+        assert!(std::str::from_utf8(&content[start..end]).unwrap().ends_with("中文\n"));
+        assert_eq!(content.line_range(end, end + 4), (end, content.len()));
+        assert_eq!(content.line_range(content.len(), content.len() + 10), (content.len(), content.len()));
+    }
+
+    #[test]
+    fn live_logs_are_not_mapped() {
+        let dir = temp_dir();
+        let data = vec![b'a'; MMAP_THRESHOLD as usize];
+        for name in ["client_log.txt", "client_log_2024-01-01-10-00-00.txt"] {
+            std::fs::write(dir.join(name), &data).unwrap();
+        }
+        let content = LogReader::open_file(&dir.join("client_log.txt")).unwrap().into_content().unwrap();
+        assert!(!content.is_mapped());
+        // a backup written just now may still be a renamed live log
+        let content = LogReader::open_file(&dir.join("client_log_2024-01-01-10-00-00.txt")).unwrap().into_content().unwrap();
+        assert!(!content.is_mapped());
+        assert_eq!(content.len(), data.len());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn large_live_logs_are_streamed() {
+        let dir = temp_dir();
+        let path = dir.join("server_log.txt");
+        let mut data = vec![];
+        while data.len() < MMAP_THRESHOLD as usize {
+            data.extend_from_slice(format!("[00:00:{:02}]: line {}\n", data.len() % 60, data.len()).as_bytes());
+        }
+        std::fs::write(&path, &data).unwrap();
+        let LogData::Stream(mut reader) = LogReader::open_file(&path).unwrap().into_data().unwrap() else {
+            panic!("large live log is buffered");
+        };
+        let content = LogContent::Owned(data.clone());
+        for (start, end) in [(0, 10), (12345, 20000), (data.len() - 3, data.len() + 10), (100, 100)] {
+            let (line_start, line_end) = content.line_range(start, end);
+            let expected = (line_start as u64, line_end as u64, data[line_start..line_end].to_vec());
+            assert_eq!(reader.read_line_range(start as u64, end as u64).unwrap(), expected);
+        }
+        // small live logs are still read at once
+        std::fs::write(dir.join("client_log.txt"), b"[00:00:01]: a\n").unwrap();
+        let data = LogReader::open_file(&dir.join("client_log.txt")).unwrap().into_data().unwrap();
+        assert!(matches!(data, LogData::Content(LogContent::Owned(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,336 @@
+// remove private information before sharing logs
+// same value is always replaced by the same alias, so the log is still readable
+
+use std::collections::HashMap;
+use regex::{Captures, Regex};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::parser::LogComment;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, TS)]
+pub enum RedactKind {
+    #[serde(rename = "token")]
+    Token,
+    #[serde(rename = "password")]
+    Password,
+    #[serde(rename = "user")]
+    UserName,
+    #[serde(rename = "ku")]
+    KleiId,
+    #[serde(rename = "steam")]
+    SteamId,
+    /// account folder of DST saves, the 32-bit Steam account id
+    #[serde(rename = "account")]
+    AccountId,
+    #[serde(rename = "ip")]
+    IpAddress,
+    #[serde(rename = "player")]
+    Player,
+}
+
+impl RedactKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RedactKind::Token => "token",
+            RedactKind::Password => "password",
+            RedactKind::UserName => "user",
+            RedactKind::KleiId => "ku",
+            RedactKind::SteamId => "steam",
+            RedactKind::AccountId => "account",
+            RedactKind::IpAddress => "ip",
+            RedactKind::Player => "player",
+        }
+    }
+}
+
+/// replaced value and the number of occurrences
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RedactItem {
+    pub kind: RedactKind,
+    pub original: String,
+    pub alias: String,
+    pub count: usize,
+}
+
+// pds-g^KU_AbCdEf12^5^abcdefABCDEF0123456789+/=
+static TOKEN_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"pds-g\^[\w^+/=-]+").unwrap()
+});
+// -cluster_password 1234 / cluster_password = 1234
+static PASSWORD_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)(-\w*password\w*\s+|\b\w*password\w*\s*=\s*)("[^"]*"|\S+)"#).unwrap()
+});
+// -cluster_password as a single argument, the next argument is its value
+static PASSWORD_ARG_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^-\w*password\w*$").unwrap()
+});
+// C:/Users/<name>/Documents/Klei, /home/<name>/.klei, /Users/<name>/Documents
+static USER_PATH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)((?:\b[A-Z]:)?[/\\]+(?:Users|home)[/\\]+)([^/\\\s:"'<>]+)"#).unwrap()
+});
+static KLEI_ID_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\bKU_[A-Za-z0-9-]{6,}").unwrap()
+});
+static STEAM_ID_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"7656119\d{10}").unwrap()
+});
+// Klei//DoNotStarveTogether/123456789/, C:\...\DoNotStarveTogether\123456789\client_save
+static ACCOUNT_DIR_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(DoNotStarveTogether[/\\]+)(\d+)\b").unwrap()
+});
+static IP_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(\d{1,3})\.(\d{1,3})\.(\d{1,3})\.(\d{1,3})\b").unwrap()
+});
+// text before four numbers which makes them a version, eg: Version:1.0.0.12, v 2.1.0.3
+static VERSION_PREFIX_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:version|v)\s*[:=]?\s*$").unwrap()
+});
+
+/// true if the text ends with a version prefix, only the last few chars are checked
+fn is_version_prefix(before: &str) -> bool {
+    let start = before.char_indices().rev().nth(15).map(|(i, _)| i).unwrap_or(0);
+    VERSION_PREFIX_RE.is_match(&before[start..])
+}
+/// lines which contains player name
+/// eg: [Join Announcement] Wilson
+///     [Say] (KU_AbCdEf12) Wilson: hello
+///     Client authenticated: (KU_AbCdEf12) Wilson
+static PLAYER_RE: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"\[(?:Join|Leave) Announcement\] (.+)$",
+        r"\[(?:Say|Whisper|Announcement)\] \(KU_[A-Za-z0-9-]+\) ([^:]+): ",
+        r"Client authenticated: \(KU_[A-Za-z0-9-]+\) (.+)$",
+        r"\[Death Announcement\] (.+?) (?:was killed by|died)",
+    ].iter().map(|s| Regex::new(s).unwrap()).collect()
+});
+
+#[derive(Debug, Default)]
+pub struct Redactor {
+    /// (kind, original) -> alias
+    aliases: HashMap<(RedactKind, String), String>,
+    counts: HashMap<(RedactKind, String), usize>,
+    /// player names learned by `scan()`
+    players: Vec<String>,
+    player_re: Option<Regex>,
+}
+
+impl Redactor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// learn player names from log content, must be called before `redact()`
+    pub fn scan(&mut self, content: &str) {
+        for line in content.lines() {
+            for re in PLAYER_RE.iter() {
+                if let Some(m) = re.captures(line) {
+                    let name = m.get(1).unwrap().as_str().trim();
+                    if name.chars().count() >= 2 && !self.players.iter().any(|n| n == name) {
+                        self.players.push(name.to_string());
+                    }
+                }
+            }
+        }
+        // match longer names first
+        self.players.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        let pattern = self.players.iter().map(|name| {
+            let word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            format!("{}{}{}",
+                if word(name.chars().next()) { r"\b" } else { "" },
+                regex::escape(name),
+                if word(name.chars().last()) { r"\b" } else { "" })
+        }).collect::<Vec<_>>().join("|");
+        self.player_re = if pattern.is_empty() { None } else { Regex::new(&pattern).ok() };
+    }
+
+    fn alias(&mut self, kind: RedactKind, original: &str) -> String {
+        let key = (kind, original.to_string());
+        *self.counts.entry(key.clone()).or_default() += 1;
+        if let Some(alias) = self.aliases.get(&key) {
+            return alias.clone();
+        }
+        let alias = match kind {
+            RedactKind::Token | RedactKind::Password => format!("<{}>", kind.as_str()),
+            _ => {
+                let index = self.aliases.keys().filter(|(k, _)| *k == kind).count() + 1;
+                format!("<{}-{}>", kind.as_str(), index)
+            },
+        };
+        self.aliases.insert(key, alias.clone());
+        alias
+    }
+
+    /// replace all private values in text
+    pub fn redact(&mut self, content: &str) -> String {
+        let s = TOKEN_RE.replace_all(content, |m: &Captures| {
+            self.alias(RedactKind::Token, &m[0])
+        });
+        let s = PASSWORD_RE.replace_all(&s, |m: &Captures| {
+            format!("{}{}", &m[1], self.alias(RedactKind::Password, &m[2]))
+        });
+        let s = USER_PATH_RE.replace_all(&s, |m: &Captures| {
+            format!("{}{}", &m[1], self.alias(RedactKind::UserName, &m[2]))
+        });
+        let s = KLEI_ID_RE.replace_all(&s, |m: &Captures| {
+            self.alias(RedactKind::KleiId, &m[0])
+        });
+        let s = STEAM_ID_RE.replace_all(&s, |m: &Captures| {
+            self.alias(RedactKind::SteamId, &m[0])
+        });
+        let s = ACCOUNT_DIR_RE.replace_all(&s, |m: &Captures| {
+            format!("{}{}", &m[1], self.alias(RedactKind::AccountId, &m[2]))
+        });
+        let s = IP_RE.replace_all(&s, |m: &Captures| {
+            let is_ip = (1..=4).all(|i| m[i].parse::<u8>().is_ok())
+                && !is_version_prefix(&s[..m.get(0).unwrap().start()]);
+            // loopback and unspecified address are not private
+            if !is_ip || &m[1] == "127" || &m[0] == "0.0.0.0" {
+                m[0].to_string()
+            } else {
+                self.alias(RedactKind::IpAddress, &m[0])
+            }
+        });
+        match self.player_re.clone() {
+            Some(re) => re.replace_all(&s, |m: &Captures| {
+                self.alias(RedactKind::Player, &m[0])
+            }).to_string(),
+            None => s.to_string(),
+        }
+    }
+
+    /// replace private values in all text of the comment
+    pub fn redact_comment(&mut self, comment: &LogComment) -> LogComment {
+        let mut comment = comment.clone();
+        for field in comment.fields.iter_mut() {
+            field.extra = self.redact(&field.extra);
+        }
+        for error in comment.errors.iter_mut() {
+            error.message = self.redact(&error.message);
+            for line in error.traceback.iter_mut() {
+                *line = self.redact(line);
+            }
+        }
+        // each argument is redacted on its own to keep quoted ones, the value of a password option is the next argument
+        let mut is_password = false;
+        for arg in comment.system.command_line.iter_mut() {
+            let redacted = if is_password { self.alias(RedactKind::Password, arg) } else { self.redact(arg) };
+            is_password = PASSWORD_ARG_RE.is_match(arg);
+            *arg = redacted;
+        }
+        let system = &mut comment.system;
+        for value in [&mut system.os, &mut system.cpu, &mut system.gpu_vendor, &mut system.renderer, &mut system.gl_version,
+            &mut system.display, &mut system.steam_branch, &mut system.steam_build_id, &mut system.language] {
+            if let Some(value) = value.as_mut() {
+                *value = self.redact(value);
+            }
+        }
+        for diagnostic in comment.mod_diagnostics.iter_mut() {
+            for problem in diagnostic.problems.iter_mut() {
+                problem.message = self.redact(&problem.message);
+            }
+        }
+        let storage = &mut comment.storage;
+        for root in storage.persist_roots.iter_mut() {
+            *root = self.redact(root);
+        }
+        for value in [&mut storage.storage_root, &mut storage.conf_dir] {
+            if let Some(value) = value.as_mut() {
+                *value = self.redact(value);
+            }
+        }
+        if let Some(crash) = comment.ds.as_mut().and_then(|ds| ds.crash.as_mut()) {
+            *crash = self.redact(crash);
+        }
+        comment
+    }
+
+    /// replaced values, sorted by kind
+    pub fn report(&self) -> Vec<RedactItem> {
+        let mut items = self.aliases.iter().map(|((kind, original), alias)| {
+            RedactItem {
+                kind: *kind,
+                original: original.clone(),
+                alias: alias.clone(),
+                count: self.counts.get(&(*kind, original.clone())).cloned().unwrap_or_default(),
+            }
+        }).collect::<Vec<_>>();
+        items.sort_by(|a, b| (a.kind, &a.alias).cmp(&(b.kind, &b.alias)));
+        items
+    }
+}
+
+/// redact content with player names learned from itself
+pub fn redact_content(content: &str) -> String {
+    let mut redactor = Redactor::new();
+    redactor.scan(content);
+    redactor.redact(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_content;
+
+    #[test]
+    fn same_value_same_alias() {
+        let mut redactor = Redactor::new();
+        redactor.scan("[00:00:01]: [Join Announcement] Wilson\n[00:00:02]: [Join Announcement] Willow\n");
+        let text = redactor.redact("\
+(KU_AbCdEf12) Wilson 76561198000000001 from 10.0.0.2
+(KU_ZyXwVu98) Willow 76561198000000002 from 10.0.0.3
+Wilson again from 10.0.0.2 and 127.0.0.1, C:/Users/alice/Documents/Klei");
+        assert_eq!(text, "\
+(<ku-1>) <player-1> <steam-1> from <ip-1>
+(<ku-2>) <player-2> <steam-2> from <ip-2>
+<player-1> again from <ip-1> and 127.0.0.1, C:/Users/<user-1>/Documents/Klei");
+        let report = redactor.report();
+        let wilson = report.iter().find(|item| item.original == "Wilson").unwrap();
+        assert_eq!((wilson.kind, wilson.alias.as_str(), wilson.count), (RedactKind::Player, "<player-1>", 2));
+    }
+
+    #[test]
+    fn versions_are_not_addresses() {
+        let text = redact_content("Loading mod: workshop-1 (A) Version:1.0.0.12\nversion 2.1.0.3, v 1.2.3.4, server 1.2.3.4");
+        assert_eq!(text, "Loading mod: workshop-1 (A) Version:1.0.0.12\nversion 2.1.0.3, v 1.2.3.4, server <ip-1>");
+    }
+
+    #[test]
+    fn command_line_args_are_redacted_one_by_one() {
+        let comment = parse_log_content(concat!(
+            r#"[00:00:00]: Command Line Arguments: -persistent_storage_root "C:/Users/bob/My Server" "#,
+            r#"-cluster_password "open sesame" -cluster "Cluster 1" -password=hunter2 -console"#,
+            "\n",
+        ).as_bytes()).unwrap();
+        assert_eq!(comment.system.command_line[1], "C:/Users/bob/My Server");
+        let mut redactor = Redactor::new();
+        let redacted = redactor.redact_comment(&comment);
+        assert_eq!(redacted.system.command_line, vec![
+            "-persistent_storage_root", "C:/Users/<user-1>/My Server",
+            "-cluster_password", "<password>",
+            "-cluster", "Cluster 1",
+            "-password=<password>",
+            "-console",
+        ]);
+    }
+
+    #[test]
+    fn storage_and_mod_problems_are_redacted() {
+        let comment = parse_log_content(concat!(
+            "[00:00:00]: PersistRootStorage is now APP:Klei//DoNotStarveTogether/123456789/\n",
+            r#"[00:00:01]: About to start a shard with these parameters: -persistent_storage_root "C:/Users/bob/Klei" -cluster Cluster_1 -shard Caves"#,
+            "\n",
+            "[00:00:02]: ModIndex:GetModsToLoad inserting moddir, \tworkshop-1\n",
+            "[00:00:03]: Error loading mod: workshop-1 (A)! C:/Users/bob/Documents/Klei/DoNotStarveTogether/123456789/mods\n",
+        ).as_bytes()).unwrap();
+        let mut redactor = Redactor::new();
+        let redacted = redactor.redact_comment(&comment);
+        assert_eq!(redacted.storage.persist_roots, vec!["Klei//DoNotStarveTogether/<account-1>/"]);
+        assert_eq!(redacted.storage.storage_root.as_deref(), Some("C:/Users/<user-1>/Klei"));
+        let problem = &redacted.mod_diagnostics[0].problems[0];
+        assert_eq!(problem.message, "Error loading mod: workshop-1 (A)! C:/Users/<user-1>/Documents/Klei/DoNotStarveTogether/<account-1>/mods");
+        // build numbers are not account folders
+        assert_eq!(redact_content("DoNotStarveTogether build 654321"), "DoNotStarveTogether build 654321");
+    }
+}
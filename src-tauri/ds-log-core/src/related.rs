@@ -0,0 +1,156 @@
+// Files related to a log: save root, cluster dir, shards, world sessions and server logs
+// Locations are resolved from the log path first, then from save locations printed in the log
+// (see `StorageRefs`), so a log copied elsewhere can still be linked to the save on this machine.
+// Only existing files are returned.
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::cluster;
+use crate::discovery::DiscoveryRoot;
+use crate::parser::LogComment;
+use crate::path::{LogPath, PathExt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum RelatedKind {
+    /// `PersistRootStorage`, eg: `Klei/DoNotStarveTogether/123456`
+    PersistRoot,
+    ClusterDir,
+    /// shard dir, or shard zip of cloud save
+    Shard,
+    /// `<shard>/save/session/<id>`
+    Session,
+    ServerLog,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RelatedFile {
+    pub kind: RelatedKind,
+    /// eg: cluster name, or shard name
+    pub name: String,
+    pub path: String,
+}
+
+/// Klei dirs which a relative `PersistRootStorage` may point into
+fn klei_dirs(log: &LogPath, roots: &[DiscoveryRoot]) -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(path) = log.local_path() {
+        dirs.extend(path.ancestors()
+            .filter(|p| matches!(p.file_name_utf8().as_str(), "Klei" | ".klei"))
+            .map(Path::to_path_buf));
+    }
+    dirs.extend(roots.iter().filter(|r| r.is_klei_dir()).map(|r| r.path.clone()));
+    dirs
+}
+
+/// `Klei//DoNotStarveTogether/123456/` -> `DoNotStarveTogether/123456`
+fn relative_to_klei(root: &str) -> PathBuf {
+    Path::new(root).components()
+        .skip_while(|c| matches!(c, Component::Normal(s) if *s == "Klei"))
+        .collect()
+}
+
+fn push_dir(list: &mut Vec<PathBuf>, dir: PathBuf) {
+    if dir.is_dir() && !list.contains(&dir) {
+        list.push(dir);
+    }
+}
+
+/// shards of a cluster: dirs with server.ini or server_log.txt, or zips of cloud save
+fn list_shards(cluster: &Path) -> Vec<PathBuf> {
+    let Ok(read) = fs::read_dir(cluster) else { return vec![]; };
+    let mut shards = read.flatten().map(|e| e.path())
+        .filter(|p| {
+            if p.is_dir() {
+                p.join("server.ini").is_file() || p.join("server_log.txt").is_file()
+            }
+            else {
+                matches!(p.file_name_utf8().as_str(), "Master.zip" | "Caves.zip")
+            }
+        })
+        .collect::<Vec<_>>();
+    shards.sort();
+    shards
+}
+
+pub fn find_related_files(log: &LogPath, comment: &LogComment, roots: &[DiscoveryRoot]) -> Vec<RelatedFile> {
+    let refs = &comment.storage;
+    let mut result = vec![];
+    let file = |kind, name: String, path: &Path| RelatedFile {
+        kind,
+        name,
+        path: path.to_string_lossy().to_string(),
+    };
+
+    // save roots printed in log
+    let klei_dirs = klei_dirs(log, roots);
+    let mut persist_dirs = vec![];
+    for root in refs.persist_roots.iter() {
+        if Path::new(root).is_absolute() {
+            push_dir(&mut persist_dirs, PathBuf::from(root));
+            continue;
+        }
+        let rel = relative_to_klei(root);
+        for klei in klei_dirs.iter() {
+            push_dir(&mut persist_dirs, klei.join(&rel));
+        }
+    }
+    for dir in persist_dirs.iter() {
+        result.push(file(RelatedKind::PersistRoot, dir.file_name_utf8(), dir));
+    }
+
+    // cluster dirs, from log path first
+    let mut clusters = vec![];
+    if let Some(shard) = cluster::shard_path(log) {
+        if let Some(dir) = shard.parent() {
+            push_dir(&mut clusters, dir.to_path_buf());
+        }
+    }
+    for dir in persist_dirs.iter() {
+        // persist root of a shard process, eg: .../Cluster_1/Master/
+        if dir.join("cluster.ini").is_file() {
+            push_dir(&mut clusters, dir.clone());
+        }
+        else if let Some(parent) = dir.parent().filter(|p| p.join("cluster.ini").is_file()) {
+            push_dir(&mut clusters, parent.to_path_buf());
+        }
+    }
+    if let Some(name) = refs.cluster.as_ref() {
+        if let (Some(root), Some(conf)) = (refs.storage_root.as_ref(), refs.conf_dir.as_ref()) {
+            push_dir(&mut clusters, Path::new(root).join(conf).join(name));
+        }
+        for dir in persist_dirs.iter() {
+            push_dir(&mut clusters, dir.join(name));
+        }
+        if let Some(conf) = refs.conf_dir.as_ref() {
+            for klei in klei_dirs.iter() {
+                push_dir(&mut clusters, klei.join(conf).join(name));
+            }
+        }
+    }
+
+    let this_log = log.local_path().map(Path::to_path_buf);
+    for dir in clusters.iter() {
+        let name = cluster::ClusterSettings::load(dir)
+            .and_then(|c| c.cluster_name)
+            .unwrap_or_else(|| dir.file_name_utf8());
+        result.push(file(RelatedKind::ClusterDir, name, dir));
+        for shard in list_shards(dir) {
+            let shard_name = shard.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            result.push(file(RelatedKind::Shard, shard_name.clone(), &shard));
+            let server_log = shard.join("server_log.txt");
+            if server_log.is_file() && this_log.as_ref() != Some(&server_log) {
+                result.push(file(RelatedKind::ServerLog, shard_name.clone(), &server_log));
+            }
+            for id in refs.sessions.iter() {
+                let session = shard.join("save/session").join(id);
+                if session.is_dir() {
+                    result.push(file(RelatedKind::Session, shard_name.clone(), &session));
+                }
+            }
+        }
+    }
+    result
+}
@@ -0,0 +1,276 @@
+// Remote log over HTTP(S), eg: a dedicated server which serves its `server_log.txt`
+// Content is downloaded into memory on every change, changes are detected by ETag / Last-Modified.
+use std::io::{Cursor, Read};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use reqwest::blocking::{Client, Response};
+use reqwest::header;
+
+use crate::error::LogError;
+use crate::path::LogPath;
+use crate::reader::LogReader;
+use crate::source::{calc_v5, LogSource, SourceMeta};
+
+/// Max size of downloaded log.
+const MAX_DOWNLOAD_SIZE: u64 = 512 * 1024 * 1024;
+
+/// `metadata` and `version` of one poll share a HEAD response younger than this
+const HEAD_CACHE_TIME: Duration = Duration::from_secs(2);
+
+static CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default()
+});
+
+/// blocking client can not be used inside async runtime (eg: tauri commands), run it in a new thread
+fn run_blocking<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    std::thread::scope(|s| s.spawn(f).join().unwrap())
+}
+
+pub fn is_url(s: &str) -> bool {
+    s.starts_with("http://") || s.starts_with("https://")
+}
+
+/// headers of the last HEAD response, None if the request failed
+#[derive(Debug, Clone, Default)]
+struct HeadInfo {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_length: Option<String>,
+}
+
+/// time of the last HEAD request and its result
+type HeadCache = Option<(Instant, Option<HeadInfo>)>;
+
+#[derive(Debug, Clone)]
+pub struct UrlSource {
+    url: String,
+    head: Arc<Mutex<HeadCache>>,
+}
+
+impl UrlSource {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), head: Default::default() }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub(crate) fn deserialize(v: &json::JsonValue) -> Result<LogPath, String> {
+        let url = v["url"].as_str().ok_or("url not found")?;
+        Ok(LogPath::url(url))
+    }
+
+    fn http_error(&self, e: reqwest::Error) -> LogError {
+        match e.status() {
+            Some(status) if status == reqwest::StatusCode::NOT_FOUND => LogError::NotFound(self.url.clone()),
+            Some(status) if status == reqwest::StatusCode::FORBIDDEN
+                || status == reqwest::StatusCode::UNAUTHORIZED => LogError::Permission(self.url.clone()),
+            _ => LogError::Io(format!("{}: {}", self.url, e)),
+        }
+    }
+
+    /// headers of the content, from a GET if the server does not support HEAD (the body is not read)
+    fn request_head(&self) -> Option<HeadInfo> {
+        run_blocking(|| {
+            let response = CLIENT.head(&self.url).send().ok()?;
+            let response = match response.status() {
+                reqwest::StatusCode::METHOD_NOT_ALLOWED | reqwest::StatusCode::NOT_IMPLEMENTED => {
+                    CLIENT.get(&self.url).send().ok()?
+                },
+                _ => response,
+            };
+            let response = response.error_for_status().ok()?;
+            Some(HeadInfo {
+                etag: header_str(&response, header::ETAG),
+                last_modified: header_str(&response, header::LAST_MODIFIED),
+                content_length: header_str(&response, header::CONTENT_LENGTH),
+            })
+        })
+    }
+
+    fn head(&self) -> Option<HeadInfo> {
+        let mut cache = self.head.lock().unwrap();
+        match cache.as_ref() {
+            Some((time, info)) if time.elapsed() < HEAD_CACHE_TIME => info.clone(),
+            _ => {
+                let info = self.request_head();
+                *cache = Some((Instant::now(), info.clone()));
+                info
+            },
+        }
+    }
+}
+
+fn header_str(response: &Response, name: header::HeaderName) -> Option<String> {
+    response.headers().get(name).and_then(|v| v.to_str().ok()).map(|s| s.to_string())
+}
+
+impl LogSource for UrlSource {
+    fn kind(&self) -> &'static str {
+        "url"
+    }
+
+    fn label(&self) -> String {
+        format!("url-{}", calc_v5(&self.url))
+    }
+
+    fn name(&self) -> String {
+        // last segment of url path, eg: http://host/logs/server_log.txt -> server_log.txt
+        let url = self.url.split(['?', '#']).next().unwrap_or_default();
+        let path = url.split_once("://").map(|(_, s)| s).unwrap_or(url);
+        path.split_once('/')
+            .and_then(|(_, path)| path.rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("log.txt")
+            .to_string()
+    }
+
+    fn location(&self) -> String {
+        self.url.clone()
+    }
+
+    fn open(&self) -> Result<LogReader, LogError> {
+        run_blocking(|| {
+            let response = CLIENT.get(&self.url).send()
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| self.http_error(e))?;
+            let mut buf = vec![];
+            response.take(MAX_DOWNLOAD_SIZE + 1).read_to_end(&mut buf)
+                .map_err(|e| LogError::Io(format!("{}: {}", self.url, e)))?;
+            if buf.len() as u64 > MAX_DOWNLOAD_SIZE {
+                return Err(LogError::Io(format!("{}: content is too large", self.url)));
+            }
+            Ok(LogReader::ForBuffer(Cursor::new(buf)))
+        })
+    }
+
+    fn metadata(&self) -> SourceMeta {
+        match self.head() {
+            Some(info) => SourceMeta {
+                exists: true,
+                mtime: info.last_modified
+                    .and_then(|s| chrono::DateTime::parse_from_rfc2822(&s).ok())
+                    .map(|t| t.timestamp() as f64)
+                    .unwrap_or(-1.0),
+                size: info.content_length
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0),
+            },
+            None => SourceMeta::missing(),
+        }
+    }
+
+    /// recent files menu should not wait for network
+    fn exists(&self) -> bool {
+        true
+    }
+
+    fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            "url": self.url.clone(),
+        }
+    }
+
+    /// if server provides none of these headers, the content is loaded only once
+    fn version(&self) -> Option<String> {
+        let info = self.head()?;
+        Some(format!("{}:{}:{}",
+            info.etag.unwrap_or_default(),
+            info.last_modified.unwrap_or_default(),
+            info.content_length.unwrap_or_default()))
+    }
+
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const LOG: &str = "[00:00:00]: Don't Starve Together: 654321 LINUX_STEAM\n[00:00:01]: cGame::StartPlaying\n";
+
+    /// serve `LOG` at `/server_log.txt` on 127.0.0.1, returns base url and count of HEAD requests
+    fn serve(support_head: bool) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let heads = Arc::new(AtomicUsize::new(0));
+        let counter = heads.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                // skip headers
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let mut parts = request.split_whitespace();
+                let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+                if method == "HEAD" {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }
+                let (status, body) = match (method, path) {
+                    ("HEAD", _) if !support_head => ("405 Method Not Allowed", ""),
+                    (_, "/server_log.txt") => ("200 OK", LOG),
+                    _ => ("404 Not Found", "not found"),
+                };
+                let mut stream = stream;
+                write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\
+                    Last-Modified: Mon, 01 Jan 2024 10:00:00 GMT\r\nConnection: close\r\n\r\n", status, body.len()).unwrap();
+                if method != "HEAD" {
+                    stream.write_all(body.as_bytes()).unwrap();
+                }
+            }
+        });
+        (base, heads)
+    }
+
+    #[test]
+    fn get_content() {
+        let (base, _) = serve(true);
+        let path = LogPath::url(format!("{}/server_log.txt", base));
+        assert_eq!(path.get_name(), "server_log.txt");
+        assert_eq!(&*path.read_content().unwrap(), LOG.as_bytes());
+    }
+
+    #[test]
+    fn metadata_and_version_share_head() {
+        let (base, heads) = serve(true);
+        let source = UrlSource::new(format!("{}/server_log.txt", base));
+        let meta = source.metadata();
+        assert!(meta.exists);
+        assert_eq!(meta.size, LOG.len() as u64);
+        assert_eq!(meta.mtime, 1704103200.0);
+        assert_eq!(source.version().unwrap(), format!("\"v1\":Mon, 01 Jan 2024 10:00:00 GMT:{}", LOG.len()));
+        assert_eq!(heads.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn not_found() {
+        let (base, _) = serve(true);
+        let source = UrlSource::new(format!("{}/missing.txt", base));
+        assert!(matches!(source.open(), Err(LogError::NotFound(_))));
+        assert!(!source.metadata().exists);
+        assert!(source.version().is_none());
+    }
+
+    #[test]
+    fn server_without_head() {
+        let (base, _) = serve(false);
+        let source = UrlSource::new(format!("{}/server_log.txt", base));
+        assert!(source.metadata().exists);
+        assert_eq!(source.metadata().size, LOG.len() as u64);
+        assert!(source.version().is_some());
+    }
+}
@@ -0,0 +1,230 @@
+// Lua sources referenced by Lua errors, eg: `scripts/components/health.lua:123`
+// Game sources are read from `data/databundles/scripts.zip` of the game install, or from loose
+// `data/scripts` files, which the game uses when scripts.zip is not mounted.
+// Mod sources (`../mods/<moddir>/scripts/...`) are read from the installed mod folder,
+// see `ModDirs`.
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::modinfo::ModDirs;
+use crate::parser::{LogComment, LuaError};
+use crate::reader::ZipIndex;
+use crate::steam::{app_id_for_game, game_data_dirs};
+
+/// lines before and after the referenced line
+pub const DEFAULT_CONTEXT: usize = 5;
+
+/// a source location in message or traceback of a Lua error
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct LuaFrame {
+    /// mod frames only, eg: workshop-727774324
+    pub moddir: Option<String>,
+    /// eg: scripts/components/health.lua, relative to the mod folder for mod frames
+    pub file: String,
+    pub line: usize,
+}
+
+impl LuaFrame {
+    /// `scripts/components/health.lua:123` or `../mods/<moddir>/scripts/...:45`, the same text as in log
+    pub fn key(&self) -> String {
+        match self.moddir.as_ref() {
+            Some(moddir) => format!("../mods/{}/{}:{}", moddir, self.file, self.line),
+            None => format!("{}:{}", self.file, self.line),
+        }
+    }
+}
+
+/// lines around a referenced line
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SourceSnippet {
+    pub frame: LuaFrame,
+    /// where the source is read from, eg: `.../data/databundles/scripts.zip:scripts/main.lua`
+    pub origin: String,
+    /// local file which can be opened in an editor, None for sources inside scripts.zip
+    pub path: Option<String>,
+    /// line number of the first line in `lines`
+    pub start: usize,
+    pub lines: Vec<String>,
+}
+
+/// source locations in a line, in order of appearance
+/// eg: `scripts/widgets/text.lua:45 in (method) SetString (Lua) <40-60>`
+///     `[string "scripts/components/health.lua"]:123: attempt to index a nil value`
+///     `scripts/main.lua(123,1) in function ...` (DS)
+///     `../mods/workshop-727774324/scripts/widgets/foodcrafting.lua:45 in ...`
+pub fn parse_frames(line: &str) -> Vec<LuaFrame> {
+    static FRAME_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"(?:^|[^\w/.\-])(?:\.\./mods/([^/"]+)/([\w/.\-]+\.lua)|(scripts/[\w/.\-]+\.lua))(?:"\])?(?::|\()(\d+)"#).unwrap()
+    });
+    FRAME_RE.captures_iter(line)
+        .filter_map(|c| Some(LuaFrame {
+            moddir: c.get(1).map(|m| m.as_str().to_string()),
+            file: c.get(2).or(c.get(3))?.as_str().to_string(),
+            line: c[4].parse().ok()?,
+        }))
+        // never leave the game or mod folder
+        .filter(|f| !f.file.split('/').chain(f.moddir.as_deref()).any(|p| p == ".."))
+        .collect()
+}
+
+/// all referenced sources of a Lua error, in order of appearance
+pub fn error_frames(error: &LuaError) -> Vec<LuaFrame> {
+    let mut result = vec![];
+    for line in std::iter::once(error.message.as_str()).chain(error.frames()) {
+        for frame in parse_frames(line) {
+            if !result.contains(&frame) {
+                result.push(frame);
+            }
+        }
+    }
+    result
+}
+
+/// Lua scripts of a local game install
+pub struct GameScripts {
+    zips: Vec<ZipIndex>,
+    /// `data` dirs with loose `scripts` folder
+    loose: Vec<PathBuf>,
+}
+
+impl GameScripts {
+    /// probe Steam libraries for a game type, loose files are searched first if `prefer_loose`
+    pub fn probe(game_type: &str, prefer_loose: bool) -> Self {
+        let data_dirs = game_data_dirs(app_id_for_game(game_type));
+        let zips = data_dirs.iter()
+            .map(|dir| dir.join("databundles/scripts.zip"))
+            .filter(|path| path.is_file())
+            .filter_map(|path| ZipIndex::open(&path).ok())
+            .collect();
+        let loose = data_dirs.into_iter()
+            .filter(|dir| dir.join("scripts").is_dir() && (prefer_loose || !dir.join("databundles/scripts.zip").is_file()))
+            .collect();
+        Self { zips, loose }
+    }
+
+    /// Lua scripts used by the game which printed the log
+    pub fn for_log(game_type: &str, comment: &LogComment) -> Self {
+        let skipped = comment.databundles_mounting_state.get("scripts.zip") == Some(&false);
+        Self::probe(game_type, skipped)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.zips.is_empty() && self.loose.is_empty()
+    }
+
+    /// source of a script, eg: `scripts/main.lua`
+    fn read(&self, file: &str) -> Option<Source> {
+        for dir in self.loose.iter() {
+            if let Some(source) = Source::read_file(&dir.join(file)) {
+                return Some(source);
+            }
+        }
+        for zip in self.zips.iter() {
+            let mut data = vec![];
+            if zip.open_by_name(file).ok().and_then(|mut r| r.read_to_end(&mut data).ok()).is_some() {
+                return Some(Source {
+                    origin: format!("{}:{}", zip.path().display(), file),
+                    path: None,
+                    content: String::from_utf8_lossy(&data).to_string(),
+                });
+            }
+        }
+        None
+    }
+}
+
+struct Source {
+    origin: String,
+    path: Option<String>,
+    content: String,
+}
+
+impl Source {
+    fn read_file(path: &Path) -> Option<Self> {
+        let data = fs::read(path).ok()?;
+        let path = path.to_string_lossy().to_string();
+        Some(Self { origin: path.clone(), path: Some(path), content: String::from_utf8_lossy(&data).to_string() })
+    }
+
+    /// `context` lines around the referenced line, `lines` is empty if the line is out of range
+    fn snippet(self, frame: LuaFrame, context: usize) -> SourceSnippet {
+        let start = frame.line.saturating_sub(context).max(1);
+        let lines = self.content.lines()
+            .skip(start - 1)
+            .take(frame.line + context + 1 - start)
+            .map(str::to_string)
+            .collect();
+        SourceSnippet { frame, origin: self.origin, path: self.path, start, lines }
+    }
+}
+
+/// game and mod sources on this machine
+pub struct LuaSources {
+    game: GameScripts,
+    mods: ModDirs,
+}
+
+impl LuaSources {
+    /// sources used by the game which printed the log
+    pub fn for_log(game_type: &str, comment: &LogComment) -> Self {
+        Self { game: GameScripts::for_log(game_type, comment), mods: ModDirs::probe(game_type) }
+    }
+
+    pub fn snippet(&self, frame: &LuaFrame, context: usize) -> Option<SourceSnippet> {
+        let source = match frame.moddir.as_ref() {
+            Some(moddir) => Source::read_file(&self.mods.find(moddir)?.join(&frame.file))?,
+            None => self.game.read(&frame.file)?,
+        };
+        Some(source.snippet(frame.clone(), context)).filter(|s| !s.lines.is_empty())
+    }
+}
+
+/// snippets of all game and mod sources referenced by Lua errors of a log, each frame once
+pub fn load_lua_sources(comment: &LogComment, game_type: &str, context: usize) -> Vec<SourceSnippet> {
+    let sources = LuaSources::for_log(game_type, comment);
+    let mut frames: Vec<LuaFrame> = vec![];
+    for error in comment.errors.iter() {
+        for frame in error_frames(error) {
+            if !frames.contains(&frame) {
+                frames.push(frame);
+            }
+        }
+    }
+    frames.iter().filter_map(|frame| sources.snippet(frame, context)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(line: &str) -> Vec<String> {
+        parse_frames(line).iter().map(LuaFrame::key).collect()
+    }
+
+    #[test]
+    fn frames_in_log_lines() {
+        assert_eq!(keys(r#"[string "scripts/components/health.lua"]:123: attempt to index a nil value"#),
+            vec!["scripts/components/health.lua:123"]);
+        assert_eq!(keys("scripts/main.lua(123,1) in function 'f'"), vec!["scripts/main.lua:123"]);
+        let frames = parse_frames("../mods/workshop-727774324/scripts/widgets/foodcrafting.lua:45 in (method) UpdateRecipes (Lua) <40-60>");
+        assert_eq!(frames, vec![LuaFrame {
+            moddir: Some("workshop-727774324".to_string()),
+            file: "scripts/widgets/foodcrafting.lua".to_string(),
+            line: 45,
+        }]);
+        assert_eq!(keys("=[C]:-1 in (method) Foo (C) <-1--1>"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn frames_never_leave_the_folder() {
+        assert!(parse_frames("scripts/../../../etc/passwd.lua:1 in main chunk").is_empty());
+        assert!(parse_frames("../mods/../../secret/scripts/a.lua:1 in main chunk").is_empty());
+        assert!(parse_frames("../mods/workshop-1/scripts/../../../a.lua:1 in main chunk").is_empty());
+        assert_eq!(keys("../mods/workshop-1/scripts/a..b.lua:2 in main chunk"), vec!["../mods/workshop-1/scripts/a..b.lua:2"]);
+    }
+}
@@ -0,0 +1,130 @@
+// Pluggable log sources, a `LogPath` is a shared handle of any `LogSource`
+// New sources implement the trait and register a deserializer for recent files,
+// no other code need to know about them.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use uuid::Uuid;
+
+use crate::error::LogError;
+use crate::path::LogPath;
+use crate::reader::LogReader;
+
+/// metadata of a source, checked by log window for changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourceMeta {
+    pub exists: bool,
+    /// unix timestamp in seconds, -1 if not available
+    pub mtime: f64,
+    pub size: u64,
+}
+
+impl SourceMeta {
+    pub fn missing() -> Self {
+        Self { exists: false, mtime: -1.0, size: 0 }
+    }
+}
+
+pub trait LogSource: Debug + Send + Sync {
+    /// type name in serialized form, eg: "dst", must be unique between sources
+    fn kind(&self) -> &'static str;
+
+    /// unique id, also used as tauri window label (only `a-zA-Z0-9-/:_` are allowed)
+    fn label(&self) -> String;
+
+    /// file name for window title and export
+    fn name(&self) -> String;
+
+    /// full location for menu and CLI output, eg: `~/Documents/Klei/.../Master.zip:server_log.txt`
+    fn location(&self) -> String;
+
+    /// ds | dst | dyn
+    fn game_type(&self) -> &'static str {
+        "dyn"
+    }
+
+    /// file on local disk, None for remote sources
+    fn local_path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// entry of cloud save zip
+    fn is_zip(&self) -> bool {
+        false
+    }
+
+    fn open(&self) -> Result<LogReader, LogError>;
+
+    fn metadata(&self) -> SourceMeta;
+
+    /// cheap check for recent files menu, without reading content
+    fn exists(&self) -> bool {
+        self.metadata().exists
+    }
+
+    /// fields of serialized form, `type` is added by `LogPath::serialize`
+    fn to_json(&self) -> json::JsonValue;
+
+    /// a token which changes with the content, None if the source is gone
+    fn version(&self) -> Option<String> {
+        let meta = self.metadata();
+        meta.exists.then(|| format!("{}:{}", meta.mtime, meta.size))
+    }
+
+    /// can be restored from recent files, false for temporary sources
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    /// how often the log window checks `version`
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(1)
+    }
+}
+
+/// build a source from its serialized fields
+pub type Deserializer = fn(&json::JsonValue) -> Result<LogPath, String>;
+
+static DESERIALIZERS: Lazy<RwLock<HashMap<&'static str, Deserializer>>> = Lazy::new(|| {
+    let mut map: HashMap<&'static str, Deserializer> = HashMap::new();
+    crate::path::register_builtin(&mut map);
+    map.insert("archive", crate::archive::ArchiveEntry::deserialize);
+    map.insert("url", crate::remote::UrlSource::deserialize);
+    RwLock::new(map)
+});
+
+/// register a new source type, so that it can be restored from recent files
+pub fn register_source(kind: &'static str, deserializer: Deserializer) {
+    DESERIALIZERS.write().unwrap().insert(kind, deserializer);
+}
+
+pub(crate) fn deserialize(v: &json::JsonValue) -> Result<LogPath, String> {
+    let kind = v["type"].as_str().ok_or("type not found")?;
+    let deserializer = DESERIALIZERS.read().unwrap().get(kind).copied()
+        .ok_or_else(|| format!("unknown type: {}", kind))?;
+    deserializer(v)
+}
+
+/// uuid v5 of a string, used in labels
+pub fn calc_v5(s: &str) -> String {
+    let uuid = Uuid::new_v5(&Uuid::NAMESPACE_URL, s.as_bytes());
+    let uuid = uuid.as_braced().to_string();
+    uuid[1..uuid.len()-1].to_string()
+}
+
+/// replace home dir with `~`
+pub fn strip_home(s: String) -> String {
+    match std::env::home_dir().map(|p| p.to_string_lossy().to_string()) {
+        Some(dir) => {
+            if s.starts_with(&dir) {
+                format!("~{}", &s[dir.len()..])
+            } else {
+                s
+            }
+        },
+        None => s
+    }
+}
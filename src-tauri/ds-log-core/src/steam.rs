@@ -0,0 +1,143 @@
+// Steam install dir and library folders
+// Libraries are listed in `steamapps/libraryfolders.vdf` of the Steam install dir,
+// the install dir itself is always a library.
+use std::fs;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use once_cell::sync::Lazy;
+
+/// app id of Don't Starve
+pub const DS_APP_ID: &str = "219740";
+/// app id of Don't Starve Together
+pub const DST_APP_ID: &str = "322330";
+
+/// app id by game type of a log, eg: `ds`, other types are treated as DST
+pub fn app_id_for_game(game_type: &str) -> &'static str {
+    match game_type {
+        "ds" => DS_APP_ID,
+        _ => DST_APP_ID,
+    }
+}
+
+/// possible Steam install dirs on current platform, only existing ones are returned
+pub fn steam_install_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(home) = std::env::home_dir() {
+        dirs.push(home.join(".steam/steam"));
+        dirs.push(home.join(".local/share/Steam"));
+        // flatpak
+        dirs.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+        dirs.push(home.join("Library/Application Support/Steam"));
+    }
+    for var in ["ProgramFiles(x86)", "ProgramFiles"] {
+        if let Some(dir) = std::env::var_os(var) {
+            dirs.push(PathBuf::from(dir).join("Steam"));
+        }
+    }
+    dirs.extend(steamcmd_dirs());
+    dirs.retain(|dir| dir.join("steamapps").is_dir());
+    dedup_dirs(dirs)
+}
+
+/// possible steamcmd dirs on every platform, usually for dedicated servers, only existing ones are returned
+pub fn steamcmd_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(home) = std::env::home_dir() {
+        dirs.push(home.join("Steam"));
+        dirs.push(home.join("steamcmd"));
+        dirs.push(home.join(".steam/steamcmd"));
+    }
+    // eg: C:\steamcmd
+    if let Some(drive) = std::env::var_os("SystemDrive") {
+        dirs.push(PathBuf::from(format!("{}\\steamcmd", drive.to_string_lossy())));
+    }
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+/// `path` values in libraryfolders.vdf
+fn parse_library_folders(content: &str) -> Vec<PathBuf> {
+    static PATH_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#""path"\s+"((?:[^"\\]|\\.)*)""#).unwrap()
+    });
+    PATH_RE.captures_iter(content)
+        .map(|c| PathBuf::from(c[1].replace("\\\\", "\\")))
+        .collect()
+}
+
+/// all Steam library dirs, eg: `~/.local/share/Steam`, `D:\SteamLibrary`
+pub fn steam_library_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    for install in steam_install_dirs() {
+        if let Ok(content) = fs::read_to_string(install.join("steamapps/libraryfolders.vdf")) {
+            dirs.extend(parse_library_folders(&content));
+        }
+        dirs.push(install);
+    }
+    dirs.retain(|dir| dir.join("steamapps").is_dir());
+    dedup_dirs(dirs)
+}
+
+/// downloaded workshop items of an app, `<library>/steamapps/workshop/content/<appid>/<id>`
+pub fn workshop_content_dirs(app_id: &str) -> Vec<PathBuf> {
+    steam_library_dirs().into_iter()
+        .map(|lib| lib.join("steamapps/workshop/content").join(app_id))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// install dirs of a game in all Steam libraries, including the dedicated server of DST
+pub fn game_install_dirs(app_id: &str) -> Vec<PathBuf> {
+    let games: &[&str] = match app_id {
+        DS_APP_ID => &["dont_starve"],
+        DST_APP_ID => &["Don't Starve Together", "Don't Starve Together Dedicated Server"],
+        _ => &[],
+    };
+    let mut dirs = vec![];
+    for lib in steam_library_dirs() {
+        for game in games {
+            dirs.push(lib.join("steamapps/common").join(game));
+        }
+    }
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+/// `<name>` dirs of game installs, on macOS they are inside the app bundle
+fn game_content_dirs(app_id: &str, name: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    for install in game_install_dirs(app_id) {
+        dirs.push(install.join(name));
+        dirs.push(install.join("dontstarve_steam.app/Contents").join(name));
+    }
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+/// `mods` dirs of game installs, eg: `steamapps/common/Don't Starve Together/mods`
+pub fn game_mods_dirs(app_id: &str) -> Vec<PathBuf> {
+    game_content_dirs(app_id, "mods")
+}
+
+/// `data` dirs of game installs, which contain `databundles/scripts.zip`
+pub fn game_data_dirs(app_id: &str) -> Vec<PathBuf> {
+    game_content_dirs(app_id, "data")
+}
+
+/// remove dirs which point to the same place, eg: `~/.steam/steam` is a link to `~/.local/share/Steam`
+fn dedup_dirs(dirs: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = vec![];
+    let mut result = vec![];
+    for dir in dirs {
+        let key = canonical(&dir);
+        if !seen.contains(&key) {
+            seen.push(key);
+            result.push(dir);
+        }
+    }
+    result
+}
+
+fn canonical(dir: &Path) -> PathBuf {
+    fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
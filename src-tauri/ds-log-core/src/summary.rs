@@ -0,0 +1,284 @@
+// crash summary for sharing on Klei forums (BBCode), Discord/GitHub (Markdown) or plain text
+
+use crate::error::LogError;
+use crate::parser::{LogComment, Mod, ModProblemKind, SystemInfo, SystemWarning};
+
+const WORKSHOP_URL: &str = "https://steamcommunity.com/sharedfiles/filedetails/?id=";
+
+/// Max traceback lines in summary, the rest are omitted
+const MAX_TRACEBACK_LINES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SummaryFormat {
+    Plain,
+    Markdown,
+    BBCode,
+}
+
+impl SummaryFormat {
+    pub fn parse(s: &str) -> Result<Self, LogError> {
+        match s {
+            "plain" | "text" => Ok(SummaryFormat::Plain),
+            "markdown" | "md" => Ok(SummaryFormat::Markdown),
+            "bbcode" => Ok(SummaryFormat::BBCode),
+            _ => Err(LogError::Parse(format!("unknown summary format: {}", s))),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SummaryFormat::Markdown => "md",
+            SummaryFormat::Plain | SummaryFormat::BBCode => "txt",
+        }
+    }
+}
+
+/// text builder for different markup formats
+struct SummaryWriter {
+    format: SummaryFormat,
+    buf: String,
+}
+
+impl SummaryWriter {
+    /// keep brackets of names and messages from being parsed as tags or links
+    fn escape(&self, text: &str) -> String {
+        match self.format {
+            SummaryFormat::Plain => text.to_string(),
+            SummaryFormat::Markdown => text.replace('[', "\\[").replace(']', "\\]"),
+            SummaryFormat::BBCode => text.replace('[', "&#91;").replace(']', "&#93;"),
+        }
+    }
+
+    fn title(&mut self, text: &str) {
+        match self.format {
+            SummaryFormat::Plain => {
+                self.buf.push_str(&format!("{}\n{}\n", text, "=".repeat(text.chars().count())));
+            },
+            SummaryFormat::Markdown => self.buf.push_str(&format!("### {}\n", text)),
+            SummaryFormat::BBCode => self.buf.push_str(&format!("[size=4][b]{}[/b][/size]\n", text)),
+        }
+    }
+
+    fn field(&mut self, key: &str, value: &str) {
+        match self.format {
+            SummaryFormat::Plain => self.buf.push_str(&format!("{}: {}\n", key, value)),
+            SummaryFormat::Markdown => self.buf.push_str(&format!("**{}:** {}  \n", key, value)),
+            SummaryFormat::BBCode => self.buf.push_str(&format!("[b]{}:[/b] {}\n", key, value)),
+        }
+    }
+
+    fn code(&mut self, lines: &[&str]) {
+        let content = lines.join("\n");
+        match self.format {
+            SummaryFormat::Plain => {
+                for line in lines {
+                    self.buf.push_str(&format!("    {}\n", line));
+                }
+            },
+            SummaryFormat::Markdown => {
+                // avoid breaking code fence
+                self.buf.push_str(&format!("```\n{}\n```\n", content.replace("```", "'''")));
+            },
+            SummaryFormat::BBCode => self.buf.push_str(&format!("[code]{}[/code]\n", self.escape(&content))),
+        }
+    }
+
+    /// start a collapsible section (plain text only has a heading)
+    fn begin_section(&mut self, title: &str) {
+        self.buf.push('\n');
+        match self.format {
+            SummaryFormat::Plain => self.buf.push_str(&format!("{}:\n", title)),
+            SummaryFormat::Markdown => self.buf.push_str(&format!("<details><summary>{}</summary>\n\n", title)),
+            SummaryFormat::BBCode => self.buf.push_str(&format!("[spoiler=\"{}\"]\n", title)),
+        }
+    }
+
+    fn end_section(&mut self) {
+        match self.format {
+            SummaryFormat::Plain => {},
+            SummaryFormat::Markdown => self.buf.push_str("\n</details>\n"),
+            SummaryFormat::BBCode => self.buf.push_str("[/spoiler]\n"),
+        }
+    }
+
+    fn mod_link(&self, m: &Mod) -> String {
+        let name = self.escape(&m.name);
+        match (&m.workshop_id, self.format) {
+            (Some(id), SummaryFormat::Plain) => format!("{} ({}{})", name, WORKSHOP_URL, id),
+            (Some(id), SummaryFormat::Markdown) => format!("[{}]({}{})", name, WORKSHOP_URL, id),
+            (Some(id), SummaryFormat::BBCode) => format!("[url={}{}]{}[/url]", WORKSHOP_URL, id, name),
+            (None, _) => format!("{} ({})", name, self.escape(&m.moddir)),
+        }
+    }
+
+    fn list(&mut self, items: &[String]) {
+        match self.format {
+            SummaryFormat::Plain | SummaryFormat::Markdown => {
+                for item in items {
+                    self.buf.push_str(&format!("- {}\n", item));
+                }
+            },
+            SummaryFormat::BBCode => {
+                self.buf.push_str("[list]\n");
+                for item in items {
+                    self.buf.push_str(&format!("[*]{}\n", item));
+                }
+                self.buf.push_str("[/list]\n");
+            },
+        }
+    }
+}
+
+fn format_time(time: &[u32]) -> String {
+    match time {
+        [h, m, s] => format!("{}:{:02}:{:02}", h, m, s),
+        _ => "-".to_string(),
+    }
+}
+
+fn system_warnings(system: &SystemInfo) -> Vec<String> {
+    system.warnings.iter().map(|warning| match warning {
+        SystemWarning::LowMemory => match (system.available_memory_mb, system.memory_mb) {
+            (Some(available), Some(total)) => format!("low memory ({} of {} MB available)", available, total),
+            _ => "low memory".to_string(),
+        },
+        SystemWarning::Bits32 => "32-bit mode".to_string(),
+        SystemWarning::SoftwareRendering => format!("software rendering ({})", system.renderer.as_deref().unwrap_or("-")),
+    }).collect()
+}
+
+/// build a crash summary from parsed log comment
+pub fn build_summary(name: &str, comment: &LogComment, format: SummaryFormat) -> String {
+    let mut w = SummaryWriter { format, buf: String::new() };
+    w.title(&format!("Log summary: {}", name));
+    w.field("Log kind", comment.kind.label());
+    w.field("Game build", &format!("{} {} ({})",
+        comment.build_version, comment.build_platform, comment.build_arch));
+    w.field("Run time", &format_time(&comment.total_time));
+    if !comment.system.warnings.is_empty() {
+        w.field("System warnings", &system_warnings(&comment.system).join(", "));
+    }
+    if let Some(ds) = comment.ds.as_ref() {
+        let dlcs = ds.dlcs.iter().map(|d| d.label()).collect::<Vec<_>>();
+        w.field("DLC", if dlcs.is_empty() { "none".to_string() } else { dlcs.join(", ") }.as_str());
+        if let Some(world_type) = ds.world_type.as_ref() {
+            let slot = ds.save_slot.map(|n| format!(" (slot {})", n)).unwrap_or_default();
+            w.field("World", &format!("{}{}", world_type, slot));
+        }
+        if let Some(crash) = ds.crash.as_ref() {
+            w.field("Engine crash", crash);
+        }
+    }
+
+    match comment.errors.first() {
+        Some(error) => {
+            if comment.errors.len() > 1 {
+                w.field("Errors", &format!("{} (showing the first one)", comment.errors.len()));
+            }
+            let suspected = error.suspected_moddir().map(|moddir| {
+                match comment.mods.get(&moddir) {
+                    Some(m) => w.mod_link(m),
+                    None => moddir,
+                }
+            });
+            w.field("Suspected mod", suspected.as_deref().unwrap_or("-"));
+            w.buf.push('\n');
+            w.code(&[error.message.as_str()]);
+            let frames = error.frames().collect::<Vec<_>>();
+            if !frames.is_empty() {
+                let shown = frames.len().min(MAX_TRACEBACK_LINES);
+                w.begin_section(&format!("Stack traceback ({} of {} lines)", shown, frames.len()));
+                w.code(&frames[..shown]);
+                w.end_section();
+            }
+        },
+        None => {
+            w.field("Errors", "none");
+        },
+    }
+
+    let items = comment.mods.values().map(|m| {
+        match &m.version {
+            Some(version) => format!("{} v{}", w.mod_link(m), version),
+            None => w.mod_link(m),
+        }
+    }).collect::<Vec<_>>();
+    w.begin_section(&format!("Mods ({})", items.len()));
+    w.list(&items);
+    w.end_section();
+
+    if !comment.mod_diagnostics.is_empty() {
+        let items = comment.mod_diagnostics.iter().map(|d| {
+            let reasons = d.problems.iter()
+                .map(|p| match p.kind {
+                    ModProblemKind::NotLoaded => "not loaded",
+                    _ => p.message.as_str(),
+                })
+                .collect::<Vec<_>>();
+            w.escape(&format!("{} ({}): {}", d.name.as_deref().unwrap_or("?"), d.moddir, reasons.join("; ")))
+        }).collect::<Vec<_>>();
+        w.begin_section(&format!("Mod problems ({})", items.len()));
+        w.list(&items);
+        w.end_section();
+    }
+    w.buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_content;
+
+    const LOG: &str = "\
+[00:00:00]: Don't Starve Together: 654321 WIN32_STEAM
+[00:00:00]: Mode: 64-bit
+[00:00:01]: ModIndex:GetModsToLoad inserting moddir, \tmy_local_mod
+[00:00:02]: Loading mod: workshop-727774324 (Craft Pot) Version:0.15.0
+[00:00:02]: Loading mod: my_local_mod (My Local Mod)
+[00:00:03]: cGame::StartPlaying
+[00:01:10]: [string \"../mods/workshop-727774324/scripts/widgets/foodcrafting.lua\"]:45: attempt to index ```recipe```
+LUA ERROR stack traceback:
+../mods/workshop-727774324/scripts/widgets/foodcrafting.lua:45 in (method) UpdateRecipes (Lua) <40-60>
+";
+
+    fn summary(format: SummaryFormat) -> String {
+        build_summary("client_log.txt", &parse_log_content(LOG.as_bytes()).unwrap(), format)
+    }
+
+    #[test]
+    fn markdown() {
+        let text = summary(SummaryFormat::Markdown);
+        assert!(text.starts_with("### Log summary: client_log.txt\n"));
+        assert!(text.contains("**Game build:** 654321 WIN32_STEAM (64-bit)  \n"));
+        assert!(text.contains("**Suspected mod:** [Craft Pot](https://steamcommunity.com/sharedfiles/filedetails/?id=727774324)  \n"));
+        // the error message can not close the code fence
+        assert!(text.contains("attempt to index '''recipe'''\n```\n"));
+        assert!(text.contains("<details><summary>Mods (2)</summary>\n\n- [Craft Pot](https://steamcommunity.com/sharedfiles/filedetails/?id=727774324) v0.15.0\n- My Local Mod (my_local_mod)\n\n</details>\n"));
+    }
+
+    #[test]
+    fn bbcode() {
+        let text = summary(SummaryFormat::BBCode);
+        assert!(text.starts_with("[size=4][b]Log summary: client_log.txt[/b][/size]\n"));
+        assert!(text.contains("[b]Suspected mod:[/b] [url=https://steamcommunity.com/sharedfiles/filedetails/?id=727774324]Craft Pot[/url]\n"));
+        assert!(text.contains("[code]../mods/workshop-727774324/scripts/widgets/foodcrafting.lua:45 in (method) UpdateRecipes (Lua) <40-60>[/code]\n"));
+        assert!(text.contains("[spoiler=\"Mods (2)\"]\n[list]\n[*][url=https://steamcommunity.com/sharedfiles/filedetails/?id=727774324]Craft Pot[/url] v0.15.0\n[*]My Local Mod (my_local_mod)\n[/list]\n[/spoiler]\n"));
+    }
+
+    #[test]
+    fn brackets_in_names_are_escaped() {
+        let comment = parse_log_content("\
+[00:00:02]: Loading mod: workshop-1 ([API] Gem Core) Version:1.0
+[00:00:03]: [string \"scripts/main.lua\"]:1: [/code] is not a tag
+LUA ERROR stack traceback:
+scripts/main.lua:1 in (main chunk) =[C] <0-0>
+".as_bytes()).unwrap();
+        let text = build_summary("client_log.txt", &comment, SummaryFormat::Markdown);
+        assert!(text.contains("- [\\[API\\] Gem Core](https://steamcommunity.com/sharedfiles/filedetails/?id=1) v1.0\n"));
+        let text = build_summary("client_log.txt", &comment, SummaryFormat::BBCode);
+        assert!(text.contains("[*][url=https://steamcommunity.com/sharedfiles/filedetails/?id=1]&#91;API&#93; Gem Core[/url] v1.0\n"));
+        assert!(text.contains("[code]&#91;string \"scripts/main.lua\"&#93;:1: &#91;/code&#93; is not a tag[/code]\n"));
+        let text = build_summary("client_log.txt", &comment, SummaryFormat::Plain);
+        assert!(text.contains("- [API] Gem Core (https://steamcommunity.com/sharedfiles/filedetails/?id=1) v1.0\n"));
+    }
+}
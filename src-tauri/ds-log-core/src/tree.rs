@@ -0,0 +1,195 @@
+// Group discovered logs as game > branch > user > cluster > shard > log
+// The layout is recovered from file paths, so it works for every discovery root:
+//   <branch>/client_log.txt, <branch>/backup/client_log/client_log_<date>.txt
+//   <branch>/<uid>/Cluster_N/<shard>/server_log.txt (and backup/server_log/...)
+//   <branch>/<uid>/CloudSaves/<hash>/<shard>.zip:server_log.txt
+//   <conf_dir>/<cluster>/<shard>/server_log.txt (dedicated server)
+// Levels which do not apply are skipped, eg: client logs are placed right under the branch.
+use std::path::Path;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::cluster::ClusterSettings;
+use crate::ipc::LogInfo;
+use crate::path::{LogPath, PathExt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Game,
+    /// `DoNotStarveTogether`, `DoNotStarveTogetherBetaBranch`, or config dir of dedicated server
+    Branch,
+    /// numeric user id folder
+    User,
+    Cluster,
+    Shard,
+    /// a log with its backups
+    Log,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogTreeNode {
+    pub kind: NodeKind,
+    /// display name, eg: cluster name from cluster.ini
+    pub name: String,
+    /// dir of the group, or location of the current log, empty for game nodes
+    pub path: String,
+    /// latest mtime of all logs inside
+    pub mtime: f64,
+    /// log nodes only, None if only backups are left
+    pub log: Option<LogInfo>,
+    /// log nodes only, newest first
+    pub backups: Vec<LogInfo>,
+    /// cluster nodes only
+    pub cluster: Option<ClusterSettings>,
+    pub children: Vec<LogTreeNode>,
+}
+
+impl LogTreeNode {
+    fn new(kind: NodeKind, name: String, path: String) -> Self {
+        let cluster = match kind {
+            NodeKind::Cluster => ClusterSettings::load(Path::new(&path)),
+            _ => None,
+        };
+        let name = cluster.as_ref()
+            .and_then(|c| c.cluster_name.clone())
+            .unwrap_or(name);
+        Self { kind, name, path, mtime: -1.0, log: None, backups: vec![], cluster, children: vec![] }
+    }
+
+    /// total number of logs, including backups
+    pub fn count(&self) -> usize {
+        self.log.iter().count() + self.backups.len()
+            + self.children.iter().map(|c| c.count()).sum::<usize>()
+    }
+
+    fn sort(&mut self) {
+        self.children.sort_by(|a, b| b.mtime.total_cmp(&a.mtime));
+        self.backups.sort_by(|a, b| b.mtime.total_cmp(&a.mtime));
+        self.children.iter_mut().for_each(Self::sort);
+    }
+}
+
+/// a level of the tree: (kind, display name, path)
+type Group = (NodeKind, String, String);
+
+fn dir_group(kind: NodeKind, dir: &Path) -> Group {
+    (kind, dir.file_name_utf8(), dir.to_string_lossy().to_string())
+}
+
+fn is_backup_dir(dir: &Path, name: &str) -> bool {
+    dir.file_name_utf8() == name
+        && dir.parent().map(|p| p.file_name_utf8() == "backup").unwrap_or(false)
+}
+
+/// groups of a dst shard dir, eg: `.../<uid>/Cluster_1/Master`
+fn shard_groups(shard: &Path, shard_name: String) -> Vec<Group> {
+    let mut groups = vec![];
+    let cluster = shard.parent().unwrap_or(Path::new(""));
+    let mut branch = cluster.parent().unwrap_or(Path::new(""));
+    // cloud save: <uid>/CloudSaves/<hash>
+    let cloud = branch.file_name_utf8() == "CloudSaves";
+    if cloud {
+        branch = branch.parent().unwrap_or(Path::new(""));
+    }
+    if cloud || branch.file_name_utf8().chars().all(|c| c.is_ascii_digit()) {
+        let user = branch;
+        branch = user.parent().unwrap_or(Path::new(""));
+        groups.push(dir_group(NodeKind::Branch, branch));
+        groups.push(dir_group(NodeKind::User, user));
+    }
+    else {
+        groups.push(dir_group(NodeKind::Branch, branch));
+    }
+    groups.push(dir_group(NodeKind::Cluster, cluster));
+    groups.push((NodeKind::Shard, shard_name, shard.to_string_lossy().to_string()));
+    groups
+}
+
+/// all groups of a log, the last one is the log group, and whether it is a backup
+fn classify(log: &LogPath) -> Option<(Vec<Group>, bool)> {
+    let path = log.local_path()?;
+    let dir = path.parent()?;
+    let name = log.get_name();
+    let game = log.get_game_type();
+    let mut groups = vec![(NodeKind::Game, game.clone(), String::new())];
+    let main_log = |dir: &Path, name: &str| -> Group {
+        (NodeKind::Log, name.to_string(), dir.join(name).to_string_lossy().to_string())
+    };
+    match log.source().kind() {
+        "ds" => {
+            groups.push(dir_group(NodeKind::Branch, dir));
+            groups.push(main_log(dir, "log.txt"));
+            Some((groups, name != "log.txt"))
+        },
+        "dst" if name == "client_log.txt" => {
+            groups.push(dir_group(NodeKind::Branch, dir));
+            groups.push(main_log(dir, "client_log.txt"));
+            Some((groups, false))
+        },
+        "dst" if is_backup_dir(dir, "client_log") => {
+            let branch = dir.parent()?.parent()?;
+            groups.push(dir_group(NodeKind::Branch, branch));
+            groups.push(main_log(branch, "client_log.txt"));
+            Some((groups, true))
+        },
+        "dst" => {
+            let backup = is_backup_dir(dir, "server_log");
+            let shard = if backup { dir.parent()?.parent()? } else { dir };
+            groups.extend(shard_groups(shard, shard.file_name_utf8()));
+            groups.push(main_log(shard, "server_log.txt"));
+            Some((groups, backup))
+        },
+        "dstcloud" => {
+            // shard zip, eg: Caves.zip
+            let shard_name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            groups.extend(shard_groups(path, shard_name));
+            let location = format!("{}:server_log.txt", path.to_string_lossy());
+            groups.push((NodeKind::Log, "server_log.txt".to_string(), location));
+            Some((groups, name != "server_log.txt"))
+        },
+        _ => None,
+    }
+}
+
+fn insert(nodes: &mut Vec<LogTreeNode>, groups: &[Group], info: LogInfo, is_backup: bool) {
+    let Some(((kind, name, path), rest)) = groups.split_first() else { return; };
+    let index = match nodes.iter().position(|n| n.kind == *kind && n.path == *path) {
+        Some(i) => i,
+        None => {
+            nodes.push(LogTreeNode::new(*kind, name.clone(), path.clone()));
+            nodes.len() - 1
+        }
+    };
+    let node = &mut nodes[index];
+    node.mtime = node.mtime.max(info.mtime);
+    if !rest.is_empty() {
+        insert(&mut node.children, rest, info, is_backup);
+    }
+    else if is_backup {
+        node.backups.push(info);
+    }
+    else {
+        node.log = Some(info);
+    }
+}
+
+/// build tree from result of `collect_logs_from`, logs which do not match any layout are placed under the game node
+pub fn build_log_tree(logs: &[LogPath]) -> Vec<LogTreeNode> {
+    let mut nodes = vec![];
+    for log in logs {
+        let info = log.to_info();
+        match classify(log) {
+            Some((groups, is_backup)) => insert(&mut nodes, &groups, info, is_backup),
+            None => {
+                let groups = [
+                    (NodeKind::Game, log.get_game_type(), String::new()),
+                    (NodeKind::Log, log.get_name(), log.get_menu_path()),
+                ];
+                insert(&mut nodes, &groups, info, false);
+            }
+        }
+    }
+    nodes.iter_mut().for_each(LogTreeNode::sort);
+    nodes
+}
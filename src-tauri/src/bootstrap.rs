@@ -1,44 +1,11 @@
-use std::path::Path;
 use tauri::Manager;
+use ds_log_core::LogError;
 
 use crate::ds_log::LogModelState;
 
-pub trait PathExt {
-    fn file_name_utf8(&self) -> String;
-    fn mtime_f64(&self) -> f64;
-    fn file_size(&self) -> u64;
-}
-
-impl PathExt for Path {
-    fn file_name_utf8(&self) -> String {
-        self.file_name().unwrap_or_default().to_string_lossy().to_string()
-    }
-
-    fn mtime_f64(&self) -> f64 {
-        match self.metadata() {
-            Ok(meta) => {
-                match meta.modified() {
-                    Ok(time) => {
-                        time.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs_f64()
-                    }
-                    Err(_) => -1.0,
-                }
-            }
-            Err(_) => -1.0,
-        }
-    }
-
-    fn file_size(&self) -> u64 {
-        match self.metadata() {
-            Ok(meta) => meta.len(),
-            Err(_) => 0,
-        }
-    }
-}
-
 #[tauri::command]
-pub fn open_url(url: String) -> Result<(), String> {
-    webbrowser::open(&url).map_err(|e| e.to_string())
+pub fn open_url(url: String) -> Result<(), LogError> {
+    Ok(webbrowser::open(&url)?)
 }
 
 #[tauri::command]
@@ -56,13 +23,23 @@ pub fn show_file_by_label(handle: tauri::AppHandle, label: String) {
 }
 
 #[tauri::command]
-pub async fn save_file(handle: tauri::AppHandle, window: tauri::Window, default_path: String, content: String) -> Result<(), String> {
+pub async fn save_file(handle: tauri::AppHandle, window: tauri::Window, default_path: String, content: String, redact: Option<bool>) -> Result<(), LogError> {
+    let content = match redact {
+        Some(true) => ds_log_core::redact::redact_content(&content),
+        _ => content,
+    };
+    save_with_dialog(&handle, window, default_path, ("Log file", &["txt"]), content);
+    Ok(())
+}
+
+/// pop a save dialog and write content to the picked path
+pub fn save_with_dialog(handle: &tauri::AppHandle, window: tauri::Window, default_path: String, filter: (&str, &[&str]), content: String) {
     use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
     let handle2 = handle.clone();
     handle.dialog()
         .file()
         .set_file_name(default_path)
-        .add_filter("Log file", &["txt"])
+        .add_filter(filter.0, filter.1)
         .set_parent(&window)
         .save_file(move |path| {
             if let Some(path) = path {
@@ -76,5 +53,4 @@ pub async fn save_file(handle: tauri::AppHandle, window: tauri::Window, default_
                 }
             }
         });
-    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,167 @@
+// open Lua sources of crash frames in an external editor
+// the editor is a command line saved in `settings` store, eg: `code -g {file}:{line}`,
+// the file is opened with the system default app if it is not set.
+// Only sources returned by `load_lua_sources` of a window can be opened from that window,
+// and a new editor command is confirmed by the user before it is saved, any program can be run
+// by the command, so the confirmation is the only check besides the program being found.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use tauri_plugin_store::StoreExt;
+use ds_log_core::LogError;
+use ds_log_core::parser::split_command;
+
+const EDITOR_KEY: &str = "external_editor";
+
+/// local source files which each log window is allowed to open, by window label
+#[derive(Default)]
+pub struct EditorAllowList {
+    paths: Mutex<HashMap<String, HashSet<String>>>,
+}
+
+impl EditorAllowList {
+    /// replace allowed paths of a window, see `SourceSnippet.path`
+    pub fn allow(&self, label: &str, paths: impl IntoIterator<Item = String>) {
+        self.paths.lock().unwrap().insert(label.to_string(), paths.into_iter().collect());
+    }
+
+    fn is_allowed(&self, label: &str, path: &str) -> bool {
+        self.paths.lock().unwrap().get(label).is_some_and(|paths| paths.contains(path))
+    }
+}
+
+fn get_editor(handle: &tauri::AppHandle) -> Option<String> {
+    let store = handle.store("settings").ok()?;
+    store.get(EDITOR_KEY)?.as_str()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// program and args with `{file}` and `{line}` replaced, the file is appended if not used
+fn build_command(editor: &str, file: &str, line: usize) -> Option<(String, Vec<String>)> {
+    let mut args = split_command(editor);
+    if args.is_empty() {
+        return None;
+    }
+    if !args.iter().any(|a| a.contains("{file}")) {
+        args.push("{file}".to_string());
+    }
+    let mut args = args.into_iter()
+        .map(|a| a.replace("{file}", file).replace("{line}", &line.to_string()));
+    let program = args.next()?;
+    Some((program, args.collect()))
+}
+
+/// program of a command line, searched in PATH if it is not a path
+fn find_program(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let exts: &[&str] = if cfg!(windows) { &["", ".exe", ".cmd", ".bat", ".com"] } else { &[""] };
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .flat_map(|dir| exts.iter().map(move |ext| dir.join(format!("{}{}", program, ext))))
+        .find(|path| path.is_file())
+}
+
+/// the program must exist, its resolved path is shown in the confirm dialog
+fn validate_command(command: &str) -> Result<PathBuf, LogError> {
+    let (program, _) = build_command(command, "", 0)
+        .ok_or_else(|| LogError::Parse("empty editor command".to_string()))?;
+    find_program(&program)
+        .ok_or_else(|| LogError::NotFound(format!("editor program not found: {}", program)))
+}
+
+#[tauri::command]
+pub fn get_external_editor(handle: tauri::AppHandle) -> Option<String> {
+    get_editor(&handle)
+}
+
+/// empty command resets to the system default app, a new command is confirmed in a dialog
+#[tauri::command]
+pub async fn set_external_editor(handle: tauri::AppHandle, command: String) -> Result<(), LogError> {
+    use tauri_plugin_store::JsonValue as Value;
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+    let command = command.trim().to_string();
+    if !command.is_empty() && get_editor(&handle).as_ref() != Some(&command) {
+        let program = validate_command(&command)?;
+        let confirmed = handle.dialog()
+            .message(format!("Open Lua sources with this command?\n\n{}\n\nProgram: {}\n\nOnly confirm a command you entered yourself, it can run any program.", command, program.display()))
+            .title("External editor")
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancel)
+            .blocking_show();
+        if !confirmed {
+            return Err(LogError::Permission("editor command is not confirmed".to_string()));
+        }
+    }
+    let store = handle.store("settings")
+        .map_err(|e| LogError::Io(format!("failed to open settings: {}", e)))?;
+    store.set(EDITOR_KEY, Value::String(command));
+    Ok(())
+}
+
+/// open a local source file at line, only paths returned by `load_lua_sources` of this window
+#[tauri::command]
+pub fn open_in_editor(handle: tauri::AppHandle, window: tauri::Window, path: String, line: usize) -> Result<(), LogError> {
+    use tauri::Manager;
+    if !handle.state::<EditorAllowList>().is_allowed(window.label(), &path) {
+        return Err(LogError::Permission(format!("not a source of this log: {}", path)));
+    }
+    if !Path::new(&path).is_file() {
+        return Err(LogError::NotFound(format!("file not exists: {}", path)));
+    }
+    match get_editor(&handle).and_then(|editor| build_command(&editor, &path, line)) {
+        Some((program, args)) => {
+            Command::new(&program).args(args).spawn()
+                .map_err(|e| LogError::Io(format!("failed to run editor `{}`: {}", program, e)))?;
+        },
+        None => {
+            use tauri_plugin_opener::OpenerExt;
+            handle.opener().open_path(path, None::<&str>)
+                .map_err(|e| LogError::Io(format!("failed to open file: {}", e)))?;
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_program_keeps_spaces() {
+        let (program, args) = build_command(r#""C:\Program Files\Notepad++\notepad++.exe" -n{line}"#, "a b.lua", 12).unwrap();
+        assert_eq!(program, r"C:\Program Files\Notepad++\notepad++.exe");
+        assert_eq!(args, vec!["-n12", "a b.lua"]);
+    }
+
+    #[test]
+    fn placeholders_are_replaced() {
+        let (program, args) = build_command("code -g {file}:{line}", "/mods/modmain.lua", 3).unwrap();
+        assert_eq!(program, "code");
+        assert_eq!(args, vec!["-g", "/mods/modmain.lua:3"]);
+        assert_eq!(split_command(r#"subl "" x"#), vec!["subl", "", "x"]);
+        assert!(build_command("  ", "a.lua", 1).is_none());
+    }
+
+    #[test]
+    fn program_must_exist() {
+        if cfg!(unix) {
+            assert_eq!(validate_command("sh {file}").unwrap().file_name().unwrap(), "sh");
+        }
+        assert!(matches!(validate_command("no-such-editor-program {file}"), Err(LogError::NotFound(_))));
+        assert!(matches!(validate_command(" "), Err(LogError::Parse(_))));
+    }
+
+    #[test]
+    fn only_allowed_paths() {
+        let list = EditorAllowList::default();
+        list.allow("dst-1", ["/mods/a/modmain.lua".to_string()]);
+        assert!(list.is_allowed("dst-1", "/mods/a/modmain.lua"));
+        assert!(!list.is_allowed("dst-2", "/mods/a/modmain.lua"));
+        assert!(!list.is_allowed("dst-1", "/etc/passwd"));
+    }
+}
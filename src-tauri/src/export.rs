@@ -0,0 +1,41 @@
+// export commands, schema and formats are documented in `ds_log_core::export`
+
+use tauri::Manager;
+use ds_log_core::LogError;
+use ds_log_core::export::{export, ExportFormat};
+use ds_log_core::redact::Redactor;
+
+use crate::bootstrap::save_with_dialog;
+use crate::ds_log::LogModelState;
+
+fn export_by_id(app: &tauri::AppHandle, id: &str, format: ExportFormat, redact: bool) -> Result<(String, String), LogError> {
+    let state = app.state::<LogModelState>();
+    let (name, comment) = state.get_comment(id).ok_or_else(|| LogError::NotFound(format!("log not found: {}", id)))?;
+    let comment = if redact {
+        let mut redactor = Redactor::new();
+        redactor.scan(&state.get_content(id)?.to_string_lossy());
+        redactor.redact_comment(&comment)
+    } else {
+        comment
+    };
+    let content = export(&name, &comment, format);
+    Ok((name, content))
+}
+
+/// get exported text of the log
+#[tauri::command]
+pub async fn load_log_export(app: tauri::AppHandle, id: String, format: String, redact: Option<bool>) -> Result<String, LogError> {
+    let format = ExportFormat::parse(&format)?;
+    export_by_id(&app, &id, format, redact.unwrap_or(false)).map(|(_, s)| s)
+}
+
+/// save exported text to file
+#[tauri::command]
+pub async fn save_log_export(app: tauri::AppHandle, window: tauri::Window, id: String, format: String, redact: Option<bool>) -> Result<(), LogError> {
+    let format = ExportFormat::parse(&format)?;
+    let (name, content) = export_by_id(&app, &id, format, redact.unwrap_or(false))?;
+    let stem = name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(&name);
+    let ext = format.extension();
+    save_with_dialog(&app, window, format!("{}{}.{}", stem, format.file_suffix(), ext), ("Export", &[ext]), content);
+    Ok(())
+}
@@ -1,34 +1,44 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod reader;
 mod ds_log;
 mod steam_workshop;
 mod bootstrap;
 mod menu;
 mod window;
+mod summary;
+mod redact;
+mod export;
+mod roots;
+mod editor;
 
-use ds_log::{list_all_logs, load_log_abstract, load_log_init, load_log_handshake};
+use ds_log::{list_all_logs, list_log_tree, load_log_abstract, load_log_init, load_log_range, load_related_files, load_mod_meta, load_lua_sources};
 use steam_workshop::{get_steam_workshop_icon, SteamWorkshopIconManager};
-use window::{open_log};
+use window::{open_log, open_log_text};
 use menu::{open_tool_menu, setup_menu, MenuRef, RecentFileList};
 use tauri::Manager;
 use bootstrap::{open_url, show_file, show_file_by_label, save_file};
 use ds_log::LogModelState;
-
-#[macro_use]
-extern crate rental; 
+use summary::{load_log_summary, save_log_summary};
+use redact::preview_redaction;
+use export::{load_log_export, save_log_export};
+use roots::{list_log_roots, add_log_root, remove_log_root, UserRootList};
+use editor::{get_external_editor, set_external_editor, open_in_editor, EditorAllowList};
 
 fn main() {
     // ds_log::debug_parse_log("/Users/wzh/Downloads/client_log (1).txt".into());  
+    // subcommands are not accepted here, output is lost without a console on Windows, use `ds-log` instead
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .manage(MenuRef::default())
         .manage(RecentFileList::default())
+        .manage(UserRootList::default())
         .manage(LogModelState::default())
         .manage(SteamWorkshopIconManager::default())
+        .manage(EditorAllowList::default())
         .setup(|app| {
             setup_store(app)?;
             setup_menu(app)?;
@@ -36,15 +46,31 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             list_all_logs,
+            list_log_tree,
+            list_log_roots,
+            add_log_root,
+            remove_log_root,
             load_log_abstract,
             open_log,
+            open_log_text,
             open_tool_menu,
             load_log_init,
-            load_log_handshake,
+            load_log_range,
+            load_related_files,
+            load_mod_meta,
+            load_lua_sources,
+            get_external_editor,
+            set_external_editor,
+            open_in_editor,
             open_url,
             show_file,
             show_file_by_label,
             save_file,
+            load_log_summary,
+            save_log_summary,
+            preview_redaction,
+            load_log_export,
+            save_log_export,
             get_steam_workshop_icon,
             shutdown,
         ])
@@ -85,6 +111,9 @@ fn setup_store(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     let recent = tauri_plugin_store::StoreBuilder::new(app, "recent").build()?;
     let state = app.state::<RecentFileList>();
     state.load_from_store(recent);
+    let settings = tauri_plugin_store::StoreBuilder::new(app, "settings").build()?;
+    let state = app.state::<UserRootList>();
+    state.load_from_store(settings);
     Ok(())
 }
 
@@ -4,16 +4,17 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tauri::{self, Manager, Wry};
 use tauri::menu::*;
-use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 use tauri_plugin_store::Store;
 
 use crate::ds_log::LogPath;
-use crate::window::open_log_impl;
+use crate::window::{open_file_impl, open_log_impl, open_text_impl};
 
 #[derive(Debug, Clone)]
 enum MenuEvent {
     Open,
     OpenRecent(LogPath),
+    OpenClipboard,
     Settings,
     About,
 }
@@ -25,17 +26,19 @@ pub struct MenuRef {
     locale: Mutex<String>,
 }
 
-const MENU_TEXT_ZH: [&'static str; 5] = [
+const MENU_TEXT_ZH: [&'static str; 6] = [
     "打开",
     "打开最近的文件",
+    "从剪贴板打开",
     "------------",
     "设置",
     "关于",
 ];
 
-const MENU_TEXT_EN: [&'static str; 5] = [
+const MENU_TEXT_EN: [&'static str; 6] = [
     "Open",
     "Open Recent File",
+    "Open from Clipboard",
     "------------",
     "Settings",
     "About",
@@ -53,9 +56,10 @@ impl MenuRef {
                 let text = if is_zh { MENU_TEXT_ZH } else { MENU_TEXT_EN };
                 items[0].as_menuitem().unwrap().set_text(text[0])?;
                 items[1].as_submenu().unwrap().set_text(text[1])?;
-                // [2] is separator
-                items[3].as_menuitem().unwrap().set_text(text[3])?;
+                items[2].as_menuitem().unwrap().set_text(text[2])?;
+                // [3] is separator
                 items[4].as_menuitem().unwrap().set_text(text[4])?;
+                items[5].as_menuitem().unwrap().set_text(text[5])?;
             }
         }
         Ok(())
@@ -97,9 +101,10 @@ impl MenuRef {
             let items = menu.items()?;
             let mut map = self.menu_event_map.lock().unwrap();
             map.insert(items[0].id().0.to_string(), MenuEvent::Open);
-            map.insert(items[1].id().0.to_string(), MenuEvent::OpenRecent(LogPath::External("".into())));
-            map.insert(items[3].id().0.to_string(), MenuEvent::Settings);
-            map.insert(items[4].id().0.to_string(), MenuEvent::About);
+            map.insert(items[1].id().0.to_string(), MenuEvent::OpenRecent(LogPath::default()));
+            map.insert(items[2].id().0.to_string(), MenuEvent::OpenClipboard);
+            map.insert(items[4].id().0.to_string(), MenuEvent::Settings);
+            map.insert(items[5].id().0.to_string(), MenuEvent::About);
         }
         Ok(())
     }
@@ -112,6 +117,7 @@ pub fn setup_menu(app: &tauri::App) -> Result<(), tauri::Error> {
     let menu = MenuBuilder::new(handle)
         .item(&MenuItem::new(handle, "Open", true, None::<&str>)?)
         .item(&SubmenuBuilder::new(handle, "Open Recent File").build()?)
+        .item(&MenuItem::new(handle, "Open from Clipboard", true, None::<&str>)?)
         .separator()
         .item(&MenuItem::new(handle, "Settings", true, None::<&str>)?)
         .item(&MenuItem::new(handle, "About", true, None::<&str>)?)
@@ -155,19 +161,29 @@ fn handle_menu_event(handle: &tauri::AppHandle, event: MenuEvent) {
             let handle2 = handle.clone();
             handle.dialog()
                 .file()
-                .add_filter("Log file", &["txt"])
+                .add_filter("Log file", &["txt", "zip", "gz", "tgz", "tar"])
                 .pick_file(move |path| {
-                    if let Some(path) = path {
-                        open_log_impl(
-                            &handle2,
-                            LogPath::External(path.into_path().unwrap())
-                        ).ok();
+                    if let Some(Ok(path)) = path.map(|p| p.into_path()) {
+                        if let Err(e) = open_file_impl(&handle2, path) {
+                            handle2.dialog()
+                                .message(e.to_string())
+                                .kind(MessageDialogKind::Error)
+                                .show(|_| {});
+                        }
                     }
                 });
         }
         MenuEvent::OpenRecent(path) => {
             open_log_impl(handle, path).ok();
         }
+        MenuEvent::OpenClipboard => {
+            if let Err(e) = open_text_impl(handle, None, None) {
+                handle.dialog()
+                    .message(e.to_string())
+                    .kind(MessageDialogKind::Error)
+                    .show(|_| {});
+            }
+        }
         MenuEvent::Settings => {
             open_unique_window(handle, "settings", None).ok();
         }
@@ -203,6 +219,9 @@ impl RecentFileList {
 
     /// add a new file to recent list
     pub fn on_open_file(&self, path: &LogPath) {
+        if !path.source().is_persistent() {
+            return;
+        }
         let mut files = self.files.lock().unwrap();
         files.retain(|f| f != path);
         files.insert(0, path.clone());
@@ -0,0 +1,19 @@
+// redaction commands, rules are in `ds_log_core::redact`
+
+use tauri::Manager;
+use ds_log_core::LogError;
+use ds_log_core::ipc::RedactionReport;
+use ds_log_core::redact::Redactor;
+
+use crate::ds_log::LogModelState;
+
+/// preview what would be replaced in the log
+#[tauri::command]
+pub async fn preview_redaction(app: tauri::AppHandle, id: String) -> Result<RedactionReport, LogError> {
+    let content = app.state::<LogModelState>().get_content(&id)?;
+    let content = content.to_string_lossy();
+    let mut redactor = Redactor::new();
+    redactor.scan(&content);
+    redactor.redact(&content);
+    Ok(RedactionReport { items: redactor.report() })
+}
@@ -0,0 +1,95 @@
+// user-added log discovery roots, saved in `settings` store
+// built-in roots (Documents, ~/.klei, Proton prefixes, dedicated servers) are probed on every listing
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{Manager, Wry};
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_store::{Store, StoreExt};
+use ds_log_core::{builtin_roots, DiscoveryRoot, LogError, RootKind};
+
+#[derive(Default)]
+pub struct UserRootList {
+    roots: Mutex<Vec<PathBuf>>,
+}
+
+impl UserRootList {
+    pub fn load_from_store(&self, store: Arc<Store<Wry>>) {
+        let roots = store.get("log_roots").unwrap_or_default();
+        if let Some(roots) = roots.as_array() {
+            *self.roots.lock().unwrap() = roots.iter()
+                .filter_map(|v| v.as_str())
+                .map(PathBuf::from)
+                .collect();
+        }
+    }
+
+    pub fn dump_to_store(&self, store: &Store<Wry>) {
+        use tauri_plugin_store::JsonValue as Value;
+        let roots = self.roots.lock().unwrap();
+        store.set("log_roots", Value::Array(
+            roots.iter().map(|p| Value::String(p.to_string_lossy().to_string())).collect::<Vec<_>>()
+        ));
+    }
+
+    pub fn get_roots(&self) -> Vec<DiscoveryRoot> {
+        self.roots.lock().unwrap().iter().map(|p| DiscoveryRoot::new(RootKind::User, p)).collect()
+    }
+
+    fn add(&self, path: PathBuf) {
+        let mut roots = self.roots.lock().unwrap();
+        if !roots.contains(&path) {
+            roots.push(path);
+        }
+    }
+
+    fn remove(&self, path: &PathBuf) {
+        self.roots.lock().unwrap().retain(|p| p != path);
+    }
+}
+
+/// all roots for `list_all_logs`, document dir can be None if not resolved
+pub fn get_all_roots(handle: &tauri::AppHandle) -> Vec<DiscoveryRoot> {
+    let document_dir = handle.path().document_dir().ok();
+    let mut roots = builtin_roots(document_dir.as_deref());
+    roots.extend(handle.state::<UserRootList>().get_roots());
+    roots
+}
+
+fn save(handle: &tauri::AppHandle) -> Result<(), LogError> {
+    let store = handle.store("settings")
+        .map_err(|e| LogError::Io(format!("failed to open settings: {}", e)))?;
+    handle.state::<UserRootList>().dump_to_store(&store);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_log_roots(handle: tauri::AppHandle) -> Vec<DiscoveryRoot> {
+    get_all_roots(&handle)
+}
+
+/// add a dir, pop a folder picker if `path` is not provided
+#[tauri::command]
+pub async fn add_log_root(handle: tauri::AppHandle, path: Option<String>) -> Result<Vec<DiscoveryRoot>, LogError> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match handle.dialog().file().blocking_pick_folder().map(|p| p.into_path()) {
+            Some(Ok(path)) => path,
+            _ => return Ok(get_all_roots(&handle)),
+        },
+    };
+    if !path.is_dir() {
+        return Err(LogError::NotFound(format!("dir not exists: {}", path.display())));
+    }
+    // `Documents` is saved as `Documents/Klei`
+    handle.state::<UserRootList>().add(DiscoveryRoot::user(path).path);
+    save(&handle)?;
+    Ok(get_all_roots(&handle))
+}
+
+#[tauri::command]
+pub async fn remove_log_root(handle: tauri::AppHandle, path: String) -> Result<Vec<DiscoveryRoot>, LogError> {
+    handle.state::<UserRootList>().remove(&PathBuf::from(path));
+    save(&handle)?;
+    Ok(get_all_roots(&handle))
+}
@@ -0,0 +1,42 @@
+// crash summary commands, the text builder is in `ds_log_core::summary`
+
+use tauri::Manager;
+use ds_log_core::LogError;
+use ds_log_core::summary::{build_summary, SummaryFormat};
+use ds_log_core::redact::Redactor;
+
+use crate::bootstrap::save_with_dialog;
+use crate::ds_log::LogModelState;
+
+fn build_summary_by_id(app: &tauri::AppHandle, id: &str, format: SummaryFormat, redact: bool) -> Result<(String, String), LogError> {
+    let state = app.state::<LogModelState>();
+    let (name, comment) = state.get_comment(id).ok_or_else(|| LogError::NotFound(format!("log not found: {}", id)))?;
+    if redact {
+        let mut redactor = Redactor::new();
+        redactor.scan(&state.get_content(id)?.to_string_lossy());
+        let comment = redactor.redact_comment(&comment);
+        let summary = build_summary(&name, &comment, format);
+        Ok((name, redactor.redact(&summary)))
+    }
+    else {
+        Ok((name.clone(), build_summary(&name, &comment, format)))
+    }
+}
+
+/// get summary text for clipboard
+#[tauri::command]
+pub async fn load_log_summary(app: tauri::AppHandle, id: String, format: String, redact: Option<bool>) -> Result<String, LogError> {
+    let format = SummaryFormat::parse(&format)?;
+    build_summary_by_id(&app, &id, format, redact.unwrap_or(false)).map(|(_, s)| s)
+}
+
+/// save summary to file
+#[tauri::command]
+pub async fn save_log_summary(app: tauri::AppHandle, window: tauri::Window, id: String, format: String, redact: Option<bool>) -> Result<(), LogError> {
+    let format = SummaryFormat::parse(&format)?;
+    let (name, content) = build_summary_by_id(&app, &id, format, redact.unwrap_or(false))?;
+    let stem = name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(&name);
+    let ext = format.extension();
+    save_with_dialog(&app, window, format!("{}_summary.{}", stem, ext), ("Summary", &[ext]), content);
+    Ok(())
+}
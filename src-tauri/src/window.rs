@@ -1,10 +1,15 @@
 // multiply window manager
 
-use tauri::{self, Listener, Manager, WebviewUrl, WebviewWindowBuilder};
+use std::path::PathBuf;
+use tauri::{self, Emitter, Listener, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_store::StoreExt;
 
-use crate::{ds_log::{LogModelState, LogPath}, menu::RecentFileList};
+use ds_log_core::{archive, remote};
+use ds_log_core::ipc::ArchiveLogs;
+
+use crate::{ds_log::{LogError, LogModelState, LogPath}, menu::RecentFileList};
 
 pub fn open_unique_window(handle: &tauri::AppHandle, label: &str, _url: Option<String>) -> Result<(), tauri::Error> {
     match handle.get_webview_window(label) {
@@ -39,19 +44,24 @@ pub fn open_unique_window(handle: &tauri::AppHandle, label: &str, _url: Option<S
     Ok(())
 }
 
-pub fn open_log_impl(handle: &tauri::AppHandle, path: LogPath) -> Result<(), tauri::Error>
+fn window_error(e: tauri::Error) -> LogError {
+    LogError::Io(e.to_string())
+}
+
+pub fn open_log_impl(handle: &tauri::AppHandle, path: LogPath) -> Result<(), LogError>
 {
     let label = path.to_label();
-    let window = handle.get_webview_window("main").unwrap();
     match handle.get_webview_window(&label) {
         Some(window) => {
-            window.set_focus()?;
+            window.set_focus().map_err(window_error)?;
         },
         None => {
             // register model on new window
             let state = handle.state::<LogModelState>();
             let max_count = if cfg!(debug_assertions) { 2 } else { 64 };
             if state.clear_inactive(max_count / 2) >= max_count {
+                let window = handle.get_webview_window("main")
+                    .ok_or_else(|| LogError::NotFound("main window".into()))?;
                 // pop an alert, but dont raise an Error
                 handle.dialog()
                     .message("Too many windows are open. Please close some windows before opening a new one.")
@@ -63,9 +73,12 @@ pub fn open_log_impl(handle: &tauri::AppHandle, path: LogPath) -> Result<(), tau
             }
             state.register(&path);
             let state = handle.state::<RecentFileList>();
-            let store = handle.store("recent").unwrap();
+            let store = handle.store("recent")
+                .map_err(|e| LogError::Io(format!("failed to open recent file store: {}", e)))?;
             state.on_open_file(&path);
             state.dump_to_store(&store);
+            let info = serde_json::to_string(&path.to_info())
+                .map_err(|e| LogError::Parse(e.to_string()))?;
             let url = WebviewUrl::App("index.html".into());
             let window = WebviewWindowBuilder::new(handle, &label, url)
                 .title(path.get_name())
@@ -73,9 +86,9 @@ pub fn open_log_impl(handle: &tauri::AppHandle, path: LogPath) -> Result<(), tau
                 .inner_size(1000.0, 750.0)
                 .min_inner_size(400.0, 300.0)
                 .initialization_script(&format!("window.logPath = JSON.parse({});", 
-                    json::JsonValue::String(path.to_ipc()).dump()))
+                    json::JsonValue::String(info).dump()))
                 .build()
-                .unwrap();
+                .map_err(window_error)?;
             let handle = handle.clone();
             window.listen("tauri://destroyed", move |_| {
                 let state = handle.state::<LogModelState>();
@@ -86,7 +99,54 @@ pub fn open_log_impl(handle: &tauri::AppHandle, path: LogPath) -> Result<(), tau
     Ok(())
 }
 
+/// open a file picked by user, if it is an archive with more than one log,
+/// send the list to main window and let user choose one
+pub fn open_file_impl(handle: &tauri::AppHandle, filepath: PathBuf) -> Result<(), LogError> {
+    if !archive::is_archive(&filepath) {
+        return open_log_impl(handle, LogPath::external(filepath));
+    }
+    let mut logs = archive::list_logs(&filepath)?;
+    match logs.len() {
+        0 => Err(LogError::NotFound(format!("no log found in archive: {}", filepath.display()))),
+        1 => open_log_impl(handle, logs.remove(0)),
+        _ => {
+            handle.emit("archive-logs", ArchiveLogs {
+                filepath: filepath.to_string_lossy().to_string(),
+                logs: logs.iter().map(|log| log.to_info()).collect(),
+            }).map_err(window_error)
+        }
+    }
+}
+
+/// open pasted text as a log, read from clipboard if `text` is not provided
+pub fn open_text_impl(handle: &tauri::AppHandle, text: Option<String>, name: Option<String>) -> Result<(), LogError> {
+    let text = match text {
+        Some(text) => text,
+        None => handle.clipboard().read_text()
+            .map_err(|e| LogError::Io(format!("failed to read clipboard: {}", e)))?,
+    };
+    if text.trim().is_empty() {
+        return Err(LogError::NotFound("no log text to open".to_string()));
+    }
+    open_log_impl(handle, LogPath::memory(name, text))
+}
+
 #[tauri::command]
-pub async fn open_log(handle: tauri::AppHandle, filepath: String) -> Result<(), tauri::Error> {
-    open_log_impl(&handle, LogPath::External(filepath.into()))
+pub async fn open_log_text(handle: tauri::AppHandle, text: Option<String>, name: Option<String>) -> Result<(), LogError> {
+    open_text_impl(&handle, text, name)
+}
+
+/// open a log file or url, `source` is the serialized `LogPath` from `LogInfo`
+#[tauri::command]
+pub async fn open_log(handle: tauri::AppHandle, filepath: String, source: Option<String>) -> Result<(), LogError> {
+    match source {
+        Some(source) => {
+            let path = LogPath::deserialize(&source).map_err(LogError::Parse)?;
+            open_log_impl(&handle, path)
+        },
+        None if remote::is_url(&filepath) => {
+            open_log_impl(&handle, LogPath::url(filepath))
+        },
+        None => open_file_impl(&handle, filepath.into()),
+    }
 }
\ No newline at end of file
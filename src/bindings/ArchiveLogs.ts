@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogInfo } from "./LogInfo";
+
+/**
+ * payload of `archive-logs` event, sent when an opened archive has more than one log
+ */
+export type ArchiveLogs = { filepath: string, logs: Array<LogInfo>, };
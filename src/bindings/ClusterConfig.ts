@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ClusterSettings } from "./ClusterSettings";
+import type { LevelSettings } from "./LevelSettings";
+import type { ModIssue } from "./ModIssue";
+import type { ModOverride } from "./ModOverride";
+import type { ShardSettings } from "./ShardSettings";
+
+/**
+ * config files of the cluster and shard which a server log belongs to
+ */
+export type ClusterConfig = { cluster_dir: string, 
+/**
+ * shard dir or zip
+ */
+shard_path: string, cluster: ClusterSettings | null, shard: ShardSettings | null, 
+/**
+ * None if modoverrides.lua not exists
+ */
+mods: Array<ModOverride> | null, level: LevelSettings | null, 
+/**
+ * files which exist but can not be parsed, eg: `modoverrides.lua: line 3: expected `}``
+ */
+errors: Array<string>, 
+/**
+ * filled by `check_mods`
+ */
+mod_issues: Array<ModIssue>, };
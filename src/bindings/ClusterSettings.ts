@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * settings from `cluster.ini`
+ */
+export type ClusterSettings = { cluster_name: string | null, cluster_description: string | null, 
+/**
+ * survival | endless | wilderness | lavaarena | quagmire
+ */
+game_mode: string | null, max_players: number | null, pvp: boolean | null, 
+/**
+ * cooperative | social | competitive | madness
+ */
+cluster_intention: string | null, offline_cluster: boolean | null, 
+/**
+ * caves or other shards are enabled
+ */
+shard_enabled: boolean | null, };
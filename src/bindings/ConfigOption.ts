@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * a `key = value` pair, value is shown as text
+ */
+export type ConfigOption = { name: string, value: string, };
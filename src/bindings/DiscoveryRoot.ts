@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RootKind } from "./RootKind";
+
+/**
+ * a dir to search logs in
+ */
+export type DiscoveryRoot = { kind: RootKind, path: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * DLC of single-player DS
+ */
+export type DsDlc = "reign_of_giants" | "shipwrecked" | "hamlet";
@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DsDlc } from "./DsDlc";
+
+/**
+ * launch info of single-player DS, only in DS log
+ * eg: DLC0002 enabled
+ *     Loading world: shipwrecked_2
+ *     Assert failure 'inst' at ..\source\simlib\entity.cpp(123): Trace follows...
+ */
+export type DsInfo = { 
+/**
+ * enabled DLCs, also implied by world type, eg: porkland -> Hamlet
+ */
+dlcs: Array<DsDlc>, 
+/**
+ * 1-based save slot of the last world
+ */
+save_slot: number | null, 
+/**
+ * survival | cave | adventure | shipwrecked | volcano | porkland
+ */
+world_type: string | null, 
+/**
+ * save file of the last world, eg: survival_1, cave_1_2
+ */
+save_file: string | null, 
+/**
+ * engine crash message, the game exits without a Lua error screen
+ */
+crash: string | null, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConfigOption } from "./ConfigOption";
+
+/**
+ * world settings from `leveldataoverride.lua`
+ */
+export type LevelSettings = { 
+/**
+ * eg: SURVIVAL_TOGETHER
+ */
+id: string | null, name: string | null, 
+/**
+ * forest | cave
+ */
+location: string | null, overrides: Array<ConfigOption>, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * result of `load_log_abstract`, a quick scan for the log list
+ */
+export type LogAbstract = { filename: string, filepath: string, is_zip: boolean, 
+/**
+ * [hour, minute, second] of the last line
+ */
+total_time: [number, number, number], has_lua_crash: boolean, };
@@ -0,0 +1,86 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DsInfo } from "./DsInfo";
+import type { LogField } from "./LogField";
+import type { LogKind } from "./LogKind";
+import type { LuaError } from "./LuaError";
+import type { ModDiagnostic } from "./ModDiagnostic";
+import type { ModInfo } from "./ModInfo";
+import type { ModLoadProfile } from "./ModLoadProfile";
+import type { Session } from "./Session";
+import type { StorageRefs } from "./StorageRefs";
+import type { SystemInfo } from "./SystemInfo";
+
+/**
+ * annotation on the important parts of log content
+ */
+export type LogComment = { fields: Array<LogField>, 
+/**
+ * if this log contains Lua stacktrace (printed from `StackTraceToLog()`)
+ */
+has_stacktrace: boolean, 
+/**
+ * if this log contains Lua Error
+ */
+has_lua_crash: boolean, 
+/**
+ * if this log contains force crash (eg. Assertion)
+ */
+has_c_crash: boolean, 
+/**
+ * eg: 654321
+ */
+build_version: string, 
+/**
+ * eg: WIN32_STEAM
+ */
+build_platform: string, 
+/**
+ * eg: 64-bit
+ */
+build_arch: string, 
+/**
+ * ds | dst, from `Don't Starve...:` header, empty if not found
+ */
+game: string, 
+/**
+ * detected when the whole log is parsed
+ */
+kind: LogKind, 
+/**
+ * DLCs, save slot and crash of DS, None for other games
+ */
+ds: DsInfo | null, system: SystemInfo, 
+/**
+ * eg: Mounting file system databundles/klump.zip successful.
+ * true: using *.zip, false: using files (debug)
+ */
+databundles_mounting_state: { [key in string]?: boolean }, 
+/**
+ * actual mod loaded, in load order
+ * eg: Loading mod: workshop-727774324 (Craft Pot) Version:0.15.0
+ */
+mods: Array<ModInfo>, 
+/**
+ * mods which were not loaded or have load problems, sorted by moddir, filled at eof
+ */
+mod_diagnostics: Array<ModDiagnostic>, 
+/**
+ * mods in load order, with load time and registered prefabs
+ */
+mod_profile: Array<ModLoadProfile>, 
+/**
+ * Lua errors in order of appearance
+ */
+errors: Array<LuaError>, 
+/**
+ * game instances in order of appearance
+ */
+sessions: Array<Session>, 
+/**
+ * total runtime of the log, usually get from the last line
+ */
+total_time: Array<number>, 
+/**
+ * save locations, see `related::find_related_files`
+ */
+storage: StorageRefs, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Serialized as `{ kind: "io", message: "..." }` for the frontend.
+ */
+export type LogError = { "kind": "io", "message": string } | { "kind": "permission", "message": string } | { "kind": "not_found", "message": string } | { "kind": "zip", "message": string } | { "kind": "missing_entry", "message": string } | { "kind": "parse", "message": string };
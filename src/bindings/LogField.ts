@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * a content block with some information, eg: a Lua error with traceback
+ */
+export type LogField = { start: number, end: number, 
+/**
+ * eg: lua_crash
+ */
+type: string, extra: string, };
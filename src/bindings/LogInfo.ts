@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * a log file in `list_all_logs`, also injected to log window as `window.logPath`
+ */
+export type LogInfo = { 
+/**
+ * ds | dst | dyn
+ */
+game: string, filename: string, 
+/**
+ * local file, or url of remote log
+ */
+filepath: string, 
+/**
+ * unix timestamp in seconds, -1 if not available
+ */
+mtime: number, filesize: number, is_zip: boolean, 
+/**
+ * serialized `LogPath`, pass it back to `open_log` to open the exact entry
+ */
+source: string, };
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ClusterConfig } from "./ClusterConfig";
+import type { LogComment } from "./LogComment";
+import type { LogError } from "./LogError";
+
+/**
+ * result of `load_log_init`
+ */
+export type LogInitData = { label: string, active: boolean, exists: boolean, mtime: number, 
+/**
+ * why the log can not be read or parsed, the comment is empty in this case
+ */
+error: LogError | null, comment: LogComment, 
+/**
+ * config of the cluster and shard, server logs in cluster folders only
+ */
+cluster: ClusterConfig | null, 
+/**
+ * content length in bytes, the text is read by `load_log_range`
+ */
+size: number, };
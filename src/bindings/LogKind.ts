@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * which game process printed the log, detected from header and marker lines
+ */
+export type LogKind = "unknown" | "ds" | "dst_client" | "dst_server" | "dedicated" | "worldgen";
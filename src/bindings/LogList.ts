@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogInfo } from "./LogInfo";
+import type { SkippedLog } from "./SkippedLog";
+
+/**
+ * result of `list_all_logs`
+ */
+export type LogList = { logs: Array<LogInfo>, 
+/**
+ * found but not readable, eg: a corrupted cloud save
+ */
+skipped: Array<SkippedLog>, };
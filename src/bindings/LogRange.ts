@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * result of `load_log_range`
+ */
+export type LogRange = { start: number, 
+/**
+ * end of the last line, start of the next range
+ */
+end: number, text: string, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogTreeNode } from "./LogTreeNode";
+import type { SkippedLog } from "./SkippedLog";
+
+/**
+ * result of `list_log_tree`
+ */
+export type LogTree = { nodes: Array<LogTreeNode>, skipped: Array<SkippedLog>, };
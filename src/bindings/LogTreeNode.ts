@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ClusterSettings } from "./ClusterSettings";
+import type { LogInfo } from "./LogInfo";
+import type { NodeKind } from "./NodeKind";
+
+export type LogTreeNode = { kind: NodeKind, 
+/**
+ * display name, eg: cluster name from cluster.ini
+ */
+name: string, 
+/**
+ * dir of the group, or location of the current log, empty for game nodes
+ */
+path: string, 
+/**
+ * latest mtime of all logs inside
+ */
+mtime: number, 
+/**
+ * log nodes only, None if only backups are left
+ */
+log: LogInfo | null, 
+/**
+ * log nodes only, newest first
+ */
+backups: Array<LogInfo>, 
+/**
+ * cluster nodes only
+ */
+cluster: ClusterSettings | null, children: Array<LogTreeNode>, };
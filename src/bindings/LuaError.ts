@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Lua error block, message line followed by traceback lines
+ * eg: [00:01:10]: [string "scripts/widgets/text.lua"]:45: attempt to index a nil value
+ *     LUA ERROR stack traceback:
+ *     scripts/widgets/text.lua:45 in (method) SetString (Lua) <40-60>
+ */
+export type LuaError = { 
+/**
+ * line number of the error message
+ */
+line: number, message: string, 
+/**
+ * raw traceback lines, including local variable dumps
+ */
+traceback: Array<string>, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * a source location in message or traceback of a Lua error
+ */
+export type LuaFrame = { 
+/**
+ * mod frames only, eg: workshop-727774324
+ */
+moddir: string | null, 
+/**
+ * eg: scripts/components/health.lua, relative to the mod folder for mod frames
+ */
+file: string, line: number, };
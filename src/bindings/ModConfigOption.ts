@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConfigOption } from "./ConfigOption";
+
+/**
+ * an item of `configuration_options`
+ */
+export type ModConfigOption = { name: string, label: string | null, default: string | null, 
+/**
+ * description and data of each choice
+ */
+choices: Array<ConfigOption>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ModProblem } from "./ModProblem";
+
+/**
+ * why a mod was not loaded, or has problems while loading
+ */
+export type ModDiagnostic = { moddir: string, 
+/**
+ * from `Loading mod:` line, None if never loaded
+ */
+name: string | null, loaded: boolean, problems: Array<ModProblem>, };
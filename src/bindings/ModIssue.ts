@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ModIssueKind } from "./ModIssueKind";
+
+export type ModIssue = { moddir: string, 
+/**
+ * mod name from log, if loaded
+ */
+name: string | null, kind: ModIssueKind, };
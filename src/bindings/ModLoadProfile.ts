@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * load order and load time of a mod
+ * eg: Loading mod: workshop-727774324 (Craft Pot) Version:0.15.0
+ *     Mod: workshop-727774324 (Craft Pot)\tLoading modmain.lua
+ *     Mod: workshop-727774324 (Craft Pot)\tRegistering prefabs
+ *     Mod: workshop-727774324 (Craft Pot)\t  Registering prefab file: prefabs/foodcooked
+ */
+export type ModLoadProfile = { moddir: string, name: string, 
+/**
+ * 1-based load order, by first appearance in `Loading mod:` or `Mod:` lines
+ */
+order: number, 
+/**
+ * seconds spent in modworldgenmain.lua and modmain.lua, summed if the mod is loaded more than once
+ */
+load_seconds: number, 
+/**
+ * seconds spent in registering prefabs
+ */
+prefab_seconds: number, prefab_files: number, prefabs: number, };
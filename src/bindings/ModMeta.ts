@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ModConfigOption } from "./ModConfigOption";
+
+export type ModMeta = { 
+/**
+ * eg: workshop-727774324
+ */
+moddir: string, 
+/**
+ * mod folder on local disk
+ */
+path: string, name: string | null, author: string | null, description: string | null, version: string | null, api_version: number | null, dst_compatible: boolean | null, client_only_mod: boolean | null, all_clients_require_mod: boolean | null, server_only_mod: boolean | null, 
+/**
+ * moddir or name of required mods, from `mod_dependencies`
+ */
+dependencies: Array<string>, options: Array<ModConfigOption>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConfigOption } from "./ConfigOption";
+
+/**
+ * a mod in `modoverrides.lua`
+ */
+export type ModOverride = { 
+/**
+ * eg: workshop-727774324
+ */
+moddir: string, enabled: boolean, options: Array<ConfigOption>, };
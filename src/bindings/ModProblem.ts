@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ModProblemKind } from "./ModProblemKind";
+
+export type ModProblem = { kind: ModProblemKind, 
+/**
+ * line number of the reason, 0 for `NotLoaded`
+ */
+line: number, 
+/**
+ * reason line without time prefix
+ */
+message: string, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ModProblemKind = "not_loaded" | "disabled_for_crash" | "outdated_api" | "missing_dependency" | "workshop_failure" | "out_of_date" | "load_error";
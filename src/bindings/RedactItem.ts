@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RedactKind } from "./RedactKind";
+
+/**
+ * replaced value and the number of occurrences
+ */
+export type RedactItem = { kind: RedactKind, original: string, alias: string, count: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RedactItem } from "./RedactItem";
+
+/**
+ * result of `preview_redaction`
+ */
+export type RedactionReport = { items: Array<RedactItem>, };
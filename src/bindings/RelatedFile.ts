@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RelatedKind } from "./RelatedKind";
+
+export type RelatedFile = { kind: RelatedKind, 
+/**
+ * eg: cluster name, or shard name
+ */
+name: string, path: string, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type RelatedKind = "persist_root" | "cluster_dir" | "shard" | "session" | "server_log";
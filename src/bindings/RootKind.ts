@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * where a root is found
+ */
+export type RootKind = "documents" | "home" | "one_drive" | "proton" | "dedicated" | "user";
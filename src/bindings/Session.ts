@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * a game instance, starts from `cGame::StartPlaying`
+ */
+export type Session = { start: number, 
+/**
+ * last line of this session, 0 if not finished
+ */
+end: number, 
+/**
+ * time prefix of the first line, eg: [0, 0, 1]
+ */
+start_time: Array<number>, };
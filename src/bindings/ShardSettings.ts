@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * settings from `server.ini` of a shard
+ */
+export type ShardSettings = { 
+/**
+ * eg: Caves
+ */
+name: string | null, is_master: boolean | null, id: string | null, server_port: number | null, };
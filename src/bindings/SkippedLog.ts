@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogError } from "./LogError";
+
+/**
+ * a log file (or zip archive) which is found but not readable
+ */
+export type SkippedLog = { path: string, error: LogError, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LuaFrame } from "./LuaFrame";
+
+/**
+ * lines around a referenced line
+ */
+export type SourceSnippet = { frame: LuaFrame, 
+/**
+ * where the source is read from, eg: `.../data/databundles/scripts.zip:scripts/main.lua`
+ */
+origin: string, 
+/**
+ * local file which can be opened in an editor, None for sources inside scripts.zip
+ */
+path: string | null, 
+/**
+ * line number of the first line in `lines`
+ */
+start: number, lines: Array<string>, };
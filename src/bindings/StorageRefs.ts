@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * save locations printed in log, used to find related files
+ * eg: PersistRootStorage is now APP:Klei//DoNotStarveTogether/123456/
+ *     About to start a shard with these parameters: ... -conf_dir DoNotStarveTogether -cluster Cluster_1 -shard Caves
+ */
+export type StorageRefs = { 
+/**
+ * path after `APP:`, eg: Klei//DoNotStarveTogether/123456/
+ */
+persist_roots: Array<string>, 
+/**
+ * `-persistent_storage_root` of shard command line
+ */
+storage_root: string | null, conf_dir: string | null, 
+/**
+ * eg: Cluster_1
+ */
+cluster: string | null, 
+/**
+ * eg: Master, Caves
+ */
+shards: Array<string>, 
+/**
+ * world session ids, eg: 3C8B4D1F1F61F9E5
+ */
+sessions: Array<string>, };
@@ -0,0 +1,51 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SystemWarning } from "./SystemWarning";
+
+/**
+ * launch and system info of the game process, from lines before the first game instance
+ * eg: Steam Branch: public
+ *     \tAvailable Physical Memory: 8541m/16291m
+ *     GL_RENDERER: ANGLE (NVIDIA GeForce GTX 1060 Direct3D11 vs_5_0 ps_5_0)
+ *     CreateWindow: Requesting 1920,1080 - 5/6/5 - -1/-1/-1 - 0
+ */
+export type SystemInfo = { os: string | null, cpu: string | null, 
+/**
+ * total physical memory in MB
+ */
+memory_mb: number | null, 
+/**
+ * available physical memory at launch in MB
+ */
+available_memory_mb: number | null, 
+/**
+ * GL_VENDOR
+ */
+gpu_vendor: string | null, 
+/**
+ * GL_RENDERER
+ */
+renderer: string | null, 
+/**
+ * GL_VERSION
+ */
+gl_version: string | null, 
+/**
+ * requested window size, eg: 1920x1080
+ */
+display: string | null, 
+/**
+ * own command line arguments, args of started shards are not included
+ */
+command_line: Array<string>, 
+/**
+ * eg: public
+ */
+steam_branch: string | null, steam_build_id: string | null, 
+/**
+ * eg: english
+ */
+language: string | null, 
+/**
+ * filled at eof
+ */
+warnings: Array<SystemWarning>, };
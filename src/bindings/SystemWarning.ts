@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * unusual launch info, shown in crash summaries
+ */
+export type SystemWarning = "low_memory" | "32_bit" | "software_rendering";
@@ -1,12 +1,19 @@
 import { invoke } from "@tauri-apps/api/core"
+import { listen } from "@tauri-apps/api/event"
 import React, { useEffect, useRef, useState } from "react"
 import white from "../../assets/white_rect.png"
 import white_70 from "../../assets/white_rect_70.png"
 import ModifiedTime from "../ModifiedTime"
-import { getName } from "../../util"
-import { useIntersectionObserver } from "../../hooks"
+import { formatError, getName } from "../../util"
+import { useIntersectionObserver, useSetting } from "../../hooks"
 import { formatRunTime } from "../RunTime"
 import { Trans, useLingui } from "@lingui/react/macro"
+import type { LogInfo } from "../../bindings/LogInfo"
+import type { LogTree } from "../../bindings/LogTree"
+import type { LogTreeNode } from "../../bindings/LogTreeNode"
+import type { SkippedLog } from "../../bindings/SkippedLog"
+import type { LogAbstract } from "../../bindings/LogAbstract"
+import type { ArchiveLogs } from "../../bindings/ArchiveLogs"
 
 
 const rectStyle: React.CSSProperties = {
@@ -15,55 +22,69 @@ const rectStyle: React.CSSProperties = {
   backgroundRepeat: "no-repeat",
 }
 
-type LogData = {
-  game: "ds" | "dst",
-  filepath: string,
-  filename: string,
-  is_zip: boolean,
-  mtime: number,
-  filesize: number,
-  has_lua_crash: boolean,
-  has_c_crash: boolean,
-  total_time: [string, string, string],
-}
-
-function diffStringList(a: string[], b: string[]) {
-  if (a.length !== b.length) {
-    return true
+/** all logs in the tree, newest first */
+function flattenTree(nodes: LogTreeNode[]): LogInfo[] {
+  const result: LogInfo[] = []
+  const walk = (node: LogTreeNode)=> {
+    if (node.log) result.push(node.log)
+    result.push(...node.backups)
+    node.children.forEach(walk)
   }
-  for (let i = 0; i < a.length; i++) {
-    if (a[i] !== b[i]) {
-      return true
-    }
-  }
-  return false
+  nodes.forEach(walk)
+  return result.sort((a, b)=> b.mtime - a.mtime)
 }
 
 export default function LogList() {
-  const [logs, setLogs] = useState<LogData[]>([])
+  const [tree, setTree] = useState<LogTreeNode[]>([])
+  const [grouped, setGrouped] = useSetting("log_list_grouped", "true")
+  const [skipped, setSkipped] = useState<SkippedLog[]>([])
   const [dirError, setDirError] = useState("")
+  // logs in the archive opened from menu, let user choose one
+  const [archive, setArchive] = useState<ArchiveLogs | null>(null)
   // const [loading, setLoading] = useState(false)
 
   useEffect(()=> {
-    let lastResult = [""]
+    let lastResult = ""
     let update = ()=> {
-      invoke<string[]>("list_all_logs").then(
+      invoke<LogTree>("list_log_tree").then(
         res=> {
-          if (diffStringList(res, lastResult)) {
+          const result = JSON.stringify(res)
+          if (result !== lastResult) {
             console.log("Update log list")
-            setLogs(res.map((v)=> {
-              let data = JSON.parse(v)
-              return data
-            }))
-            lastResult = res
+            setTree(res.nodes)
+            setSkipped(res.skipped)
+            lastResult = result
           }
         },
-        setDirError,
+        err=> setDirError(formatError(err)),
       )
     }
     update()
     const timer = setInterval(update, 1000 * 10)
-    return ()=> clearInterval(timer)
+    // log folders changed in settings
+    const unlisten = listen<{key: string}>("setting", e=> {
+      if (e.payload.key === "log_roots") update()
+    })
+    return ()=> {
+      clearInterval(timer)
+      unlisten.then(f=> f())
+    }
+  }, [])
+
+  useEffect(()=> {
+    const unlisten = listen<ArchiveLogs>("archive-logs", e=> setArchive(e.payload))
+    return ()=> { unlisten.then(f=> f()) }
+  }, [])
+
+  // paste log text (eg: copied from Discord) to open it directly
+  useEffect(()=> {
+    const onPaste = (e: ClipboardEvent)=> {
+      const text = e.clipboardData?.getData("text/plain")
+      if (text && text.trim())
+        invoke("open_log_text", {text}).catch(err=> setDirError(formatError(err)))
+    }
+    document.addEventListener("paste", onPaste)
+    return ()=> document.removeEventListener("paste", onPaste)
   }, [])
 
   return (
@@ -76,11 +97,50 @@ export default function LogList() {
           </p>
         </div>
       }
+      {
+        skipped.length > 0 &&
+        <div className="bg-yellow-100/70 p-2 m-2 rounded-sm border-yellow-500 border-1">
+          <p className="text-yellow-700 select-text">
+            <Trans>Skipped {skipped.length} unreadable log(s):</Trans>
+          </p>
+          {
+            skipped.map(v=> <p key={v.path} className="text-sm text-yellow-700 select-text break-all">
+              {formatError(v.error)}
+            </p>)
+          }
+        </div>
+      }
+      {
+        archive &&
+        <div className="bg-blue-100/70 p-2 m-2 rounded-sm border-blue-500 border-1">
+          <p className="text-blue-700 select-text break-all">
+            <Trans>Choose a log in {getName(archive.filepath)}:</Trans>
+            <span className="float-right cursor-pointer px-1" onClick={()=> setArchive(null)}>✕</span>
+          </p>
+          {
+            archive.logs.map(v=> <p key={v.source}
+              className="text-sm text-blue-700 cursor-pointer hover:underline break-all"
+              onClick={()=> invoke("open_log", {filepath: v.filepath, source: v.source}).then(
+                ()=> setArchive(null),
+                err=> setDirError(formatError(err)))}>
+              {JSON.parse(v.source).entries.join(" > ")}
+            </p>)
+          }
+        </div>
+      }
       {
         // loading ? <p>Loading...</p> : null
       }
+      <p className="text-right text-sm px-2 mb-1">
+        <span className="cursor-pointer text-gray-100 hover:text-blue-300"
+          onClick={()=> setGrouped(grouped === "true" ? "false" : "true")}>
+          {grouped === "true" ? <Trans>Show as list</Trans> : <Trans>Group by cluster</Trans>}
+        </span>
+      </p>
       {
-        logs.map(v=> <LogItem {...v} key={`[${v.is_zip ? "zip": "file"}]${v.filepath}-${v.filename}`}/>)
+        grouped === "true" ?
+          tree.map(v=> <TreeNode node={v} key={v.kind + v.path + v.name}/>) :
+          flattenTree(tree).map(v=> <LogItem {...v} key={v.source}/>)
       }
       <p className="mb-10 text-center">
         <span className="underline cursor-pointer p-1 text-gray-100 hover:text-blue-300"
@@ -106,26 +166,76 @@ export default function LogList() {
   )
 }
 
-function LogItem(props: LogData) {
-  const {filepath, filename, is_zip} = props
+function TreeNode({node}: {node: LogTreeNode}) {
+  // clusters with more than two shards start collapsed
+  const [open, setOpen] = useState(node.kind !== "cluster" || node.children.length <= 2)
+  const [showBackups, setShowBackups] = useState(false)
+  const {t} = useLingui()
+
+  if (node.kind === "log") {
+    const current = node.log || node.backups[0]
+    const backups = node.log ? node.backups : node.backups.slice(1)
+    return (
+      <div>
+        { current && <LogItem {...current}/> }
+        {
+          backups.length > 0 &&
+          <p className="text-sm text-gray-100 px-2 mb-2 cursor-pointer hover:text-blue-300"
+            onClick={()=> setShowBackups(!showBackups)}>
+            {showBackups ? "▾" : "▸"} <Trans>{backups.length} backup(s)</Trans>
+          </p>
+        }
+        {
+          showBackups && <div className="pl-4">
+            { backups.map(v=> <LogItem {...v} key={v.source}/>) }
+          </div>
+        }
+      </div>
+    )
+  }
+
+  const title = node.kind === "game" ?
+    (node.name === "ds" ? "Don't Starve" : "Don't Starve Together") :
+    node.kind === "user" ? t`User ${node.name}` :
+    node.name
+  const cluster = node.cluster
+  return (
+    <div className={node.kind === "game" ? "" : "pl-2"}>
+      <p className="text-gray-100 px-1 py-0.5 cursor-pointer select-none hover:text-blue-300 truncate"
+        title={node.path}
+        onClick={()=> setOpen(!open)}>
+        {open ? "▾" : "▸"} <span className={node.kind === "cluster" ? "font-bold" : ""}>{title}</span>
+        {
+          cluster && cluster.game_mode &&
+          <span className="text-sm opacity-60 ml-2">
+            {cluster.game_mode}{cluster.max_players ? ` · ${cluster.max_players}P` : ""}{cluster.pvp ? " · PvP" : ""}
+          </span>
+        }
+      </p>
+      {
+        open && node.children.map(v=> <TreeNode node={v} key={v.kind + v.path + v.name}/>)
+      }
+    </div>
+  )
+}
+
+function LogItem(props: LogInfo) {
+  const {filepath, filename, is_zip, source} = props
   const [hover, setHover] = useState(false)
   // const [error, setError] = useState("")
   const [hasLuaCrash, setHasLuaCrash] = useState(false)
-  const [hasCCrash, setHasCCrash] = useState(false)
   const [totalTime, setTotalTime] = useState([0, 0, 0])
-  const hasBug = hasLuaCrash || hasCCrash
+  const hasBug = hasLuaCrash
   const div = useRef<HTMLDivElement>(null)
   const {appeared} = useIntersectionObserver({ref: div})
   const {t} = useLingui()
 
   useEffect(()=> {
     if (appeared) {
-      invoke<string>("load_log_abstract", {filepath, filename, is_zip}).then(
-        res=> {
-          const data = JSON.parse(res)
+      invoke<LogAbstract>("load_log_abstract", {filepath, filename, is_zip, source}).then(
+        data=> {
           setHasLuaCrash(data.has_lua_crash)
-          setHasCCrash(data.has_c_crash)
-          setTotalTime(data.total_time.map((v: string)=> parseInt(v)))
+          setTotalTime(data.total_time)
         },
         console.error)
     }
@@ -144,7 +254,7 @@ function LogItem(props: LogData) {
       style={style}
       onMouseEnter={()=> setHover(true)}
       onMouseLeave={()=> setHover(false)}
-      onClick={()=> invoke("open_log", {filepath, filename, is_zip, from: "local"})}
+      onClick={()=> invoke("open_log", {filepath, source})}
       key={props.filepath}>
       <h1 className={"font-bold " + (hasBug ? " text-red-500" : "")}>
         {getName(props.filename)}
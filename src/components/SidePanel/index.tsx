@@ -1,16 +1,29 @@
 import { invoke } from '@tauri-apps/api/core'
 import { WebviewWindow } from '@tauri-apps/api/webviewWindow'
-import React, { useState } from 'react'
+import React, { useEffect, useState } from 'react'
 import { useLingui } from '@lingui/react/macro'
 import type { LogComment, NavigateAction } from '../../pages/LogBrowserPage'
+import type { RedactionReport } from '../../bindings/RedactionReport'
+import type { ClusterConfig } from '../../bindings/ClusterConfig'
+import type { RelatedFile } from '../../bindings/RelatedFile'
+import type { ModMeta } from '../../bindings/ModMeta'
+import type { ModDiagnostic } from '../../bindings/ModDiagnostic'
+import type { ModLoadProfile } from '../../bindings/ModLoadProfile'
+import type { ModProblemKind } from '../../bindings/ModProblemKind'
+import type { LogKind } from '../../bindings/LogKind'
+import type { DsInfo } from '../../bindings/DsInfo'
+import type { DsDlc } from '../../bindings/DsDlc'
+import type { SystemInfo } from '../../bindings/SystemInfo'
+import type { SystemWarning } from '../../bindings/SystemWarning'
 import { formatRunTime } from '../RunTime'
+import { getName } from '../../util'
 
 function showFile() {
   const label = WebviewWindow.getCurrent().label
   invoke("show_file_by_label", {label})
 }
 
-async function saveFile(content: string) {
+async function saveFile(content: string, redact: boolean) {
   let defaultPath = await WebviewWindow.getCurrent().title()
   for (let name of ["server_log", "client_log", "log"]) {
     if (defaultPath.startsWith(name)) {
@@ -18,24 +31,76 @@ async function saveFile(content: string) {
       break
     }
   }
-  invoke("save_file", { defaultPath, content })
+  invoke("save_file", { defaultPath, content, redact })
+}
+
+type SummaryFormat = "markdown" | "bbcode" | "plain"
+
+function copySummary(format: SummaryFormat, redact: boolean, message: string) {
+  const id = WebviewWindow.getCurrent().label
+  invoke<string>("load_log_summary", { id, format, redact }).then(
+    text=> {
+      navigator.clipboard.writeText(text)
+      window.alert(message)
+    },
+    console.error,
+  )
+}
+
+function saveSummary(format: SummaryFormat, redact: boolean) {
+  const id = WebviewWindow.getCurrent().label
+  invoke("save_log_summary", { id, format, redact })
+}
+
+type ExportFormat = "json" | "mods_csv" | "errors_csv"
+
+function saveExport(format: ExportFormat, redact: boolean) {
+  const id = WebviewWindow.getCurrent().label
+  invoke("save_log_export", { id, format, redact })
+}
+
+function previewRedaction(emptyMessage: string) {
+  const id = WebviewWindow.getCurrent().label
+  invoke<RedactionReport>("preview_redaction", { id }).then(
+    report=> {
+      const lines = report.items.map(v=> `${v.original} -> ${v.alias} (x${v.count})`)
+      window.alert(lines.length ? lines.join("\n") : emptyMessage)
+    },
+    console.error,
+  )
 }
 
 type SidePanelProps = {
   logContent: string,
   logComment: LogComment,
+  /** server logs in cluster folders only */
+  cluster?: ClusterConfig | null,
   navigate: (id: NavigateAction)=> void,
 }
 
 export default function SidePanel(props: SidePanelProps) {
   const comment = props.logComment || {} as LogComment
-  const { navigate, logContent } = props
+  const { navigate, logContent, cluster } = props
   const [showHelpHint, setShowHelpHint] = useState(true)
   const { t } = useLingui()
   const hasBug = comment.has_lua_crash || comment.has_c_crash // TODO: use field finding?
+  // worldgen logs end before any game instance starts
+  const isWorldgen = comment.kind === "worldgen"
 
   const [showModList, setShowModList] = useState(false)
   const [showGameInfo, setShowGameInfo] = useState(false)
+  const [showCluster, setShowCluster] = useState(false)
+  const [showModProfile, setShowModProfile] = useState(false)
+  const [related, setRelated] = useState<RelatedFile[] | null>(null)
+  const toggleRelated = ()=> {
+    if (related) return setRelated(null)
+    const label = WebviewWindow.getCurrent().label
+    invoke<RelatedFile[]>("load_related_files", {id: label}).then(setRelated, console.error)
+  }
+  const configIssues = cluster ? cluster.mod_issues.length + cluster.errors.length : 0
+  const modProblems = comment.mod_diagnostics ? comment.mod_diagnostics.length : 0
+  const systemWarnings = comment.system ? comment.system.warnings.length : 0
+  const [redact, setRedact] = useState(false)
 
   return (
     <div className="h-screen bg-blue-50 p-2 select-none text-gray-600"
@@ -52,20 +117,54 @@ export default function SidePanel(props: SidePanelProps) {
         </div>
         <Button disable={!hasBug} onClick={()=> navigate("prev-error")}>{t`Prev error info`}</Button>
         <Button disable={!hasBug} onClick={()=> navigate("next-error")}>{t`Next error info`}</Button>
-        <Button onClick={()=> navigate("prev-instance")}>{t`Prev game instance`}</Button>
-        <Button onClick={()=> navigate("next-instance")}>{t`Next game instance`}</Button>
+        <Button disable={isWorldgen} onClick={()=> navigate("prev-instance")}>{t`Prev game instance`}</Button>
+        <Button disable={isWorldgen} onClick={()=> navigate("next-instance")}>{t`Next game instance`}</Button>
         <Section title={t`Show info...`} />
-        <Button onClick={()=> setShowGameInfo(v=> !v)}>{t`Game info`}</Button>
+        <Button intent={systemWarnings > 0 ? "warning" : undefined} onClick={()=> setShowGameInfo(v=> !v)}>
+          {t`Game info`}
+        </Button>
         {
           showGameInfo && <GameInfo comment={comment}/>
         }
-        <Button onClick={()=> setShowModList(v=> !v)}>{t`Mod list`}</Button>
+        <Button intent={modProblems > 0 ? "warning" : undefined} onClick={()=> setShowModList(v=> !v)}>
+          {modProblems > 0 ? t`Mod list (${modProblems} problems)` : t`Mod list`}
+        </Button>
         {
           showModList && comment.mods && <ModList comment={comment}/>
         }
+        <Button onClick={()=> setShowModProfile(v=> !v)}>{t`Mod load time`}</Button>
+        {
+          showModProfile && comment.mod_profile && <ModProfile profile={comment.mod_profile}/>
+        }
+        {
+          cluster && <Button intent={configIssues > 0 ? "warning" : undefined}
+            onClick={()=> setShowCluster(v=> !v)}>
+            {configIssues > 0 ? t`Cluster config (${configIssues} issues)` : t`Cluster config`}
+          </Button>
+        }
+        {
+          showCluster && cluster && <ClusterInfo cluster={cluster}/>
+        }
         <Section title={t`File operation...`}/>
         <Button onClick={showFile}>{t`Reveal in folder`}</Button>
-        <Button onClick={()=> saveFile(logContent)}>{t`Save as`}</Button>
+        <Button onClick={toggleRelated}>{t`Related files`}</Button>
+        {
+          related && <RelatedFiles files={related}/>
+        }
+        <Button onClick={()=> setRedact(v=> !v)}>
+          {redact ? t`Hide private info: on` : t`Hide private info: off`}
+        </Button>
+        <Button onClick={()=> saveFile(logContent, redact)}>{t`Save as`}</Button>
+        <Section title={t`Share summary...`}/>
+        <Button onClick={()=> previewRedaction(t`No private info found.`)}>{t`Preview hidden info`}</Button>
+        <Button onClick={()=> copySummary("markdown", redact, t`Summary copied to clipboard.`)}>{t`Copy for Discord/GitHub`}</Button>
+        <Button onClick={()=> copySummary("bbcode", redact, t`Summary copied to clipboard.`)}>{t`Copy for Klei forums`}</Button>
+        <Button onClick={()=> copySummary("plain", redact, t`Summary copied to clipboard.`)}>{t`Copy as plain text`}</Button>
+        <Button onClick={()=> saveSummary("markdown", redact)}>{t`Save summary`}</Button>
+        <Section title={t`Export data...`}/>
+        <Button onClick={()=> saveExport("json", redact)}>{t`Export as JSON`}</Button>
+        <Button onClick={()=> saveExport("mods_csv", redact)}>{t`Export mod list (CSV)`}</Button>
+        <Button onClick={()=> saveExport("errors_csv", redact)}>{t`Export errors (CSV)`}</Button>
         <div className="h-40"></div>
       </div>
     </div>
@@ -94,6 +193,9 @@ function Button(props: ButtonProps) {
   if (props.intent === "danger") {
     colorClass = "bg-red-100 hover:bg-red-200 border-red-500"
   }
+  else if (props.intent === "warning") {
+    colorClass = "bg-yellow-100 hover:bg-yellow-200 border-yellow-500"
+  }
   return (
     <button
       disabled={disable} 
@@ -112,20 +214,92 @@ type GameInfoProps = {
 
 function GameInfo(props: GameInfoProps) {
   const {comment} = props
+  const { t } = useLingui()
+  const kindLabels: {[K in LogKind]: string} = {
+    unknown: t`Unknown`,
+    ds: t`DS`,
+    dst_client: t`DST client`,
+    dst_server: t`DST server shard`,
+    dedicated: t`DST dedicated server`,
+    worldgen: t`World generation`,
+  }
 
   return (
     <div className="bg-white/90 border-slate-300 border rounded-sm p-2 my-0.5 text-sm \
       max-w-full break-words select-text">
+      <p>{t`log kind`}: {kindLabels[comment.kind] || kindLabels.unknown}</p>
       <p>version: {comment.build_version}</p>
       <p>platform: {comment.build_platform}</p>
       <p>arch: {comment.build_arch}</p>
       <p>run time: {formatRunTime(comment.total_time)}</p>
+      {
+        comment.ds && <DsInfoView ds={comment.ds}/>
+      }
+      {
+        comment.system && <SystemInfoView system={comment.system} arch={comment.build_arch}/>
+      }
       {/* <p>file mounting: </p> */}
       {/* {JSON.stringify(comment.databundles_mounting_state)} */}
     </div>
   )
 }
 
+function DsInfoView(props: {ds: DsInfo}) {
+  const {ds} = props
+  const { t } = useLingui()
+  const dlcLabels: {[K in DsDlc]: string} = {
+    reign_of_giants: t`Reign of Giants`,
+    shipwrecked: t`Shipwrecked`,
+    hamlet: t`Hamlet`,
+  }
+
+  return (
+    <>
+      <p>DLC: {ds.dlcs.length > 0 ? ds.dlcs.map(d=> dlcLabels[d]).join(", ") : "-"}</p>
+      {
+        ds.world_type && <p>{t`world`}: {ds.world_type}{ds.save_slot !== null && ` (${t`slot`} ${ds.save_slot})`}</p>
+      }
+      {
+        ds.crash && <p className="text-red-500">{t`engine crash`}: {ds.crash}</p>
+      }
+    </>
+  )
+}
+
+function SystemInfoView(props: {system: SystemInfo, arch: string}) {
+  const {system, arch} = props
+  const { t } = useLingui()
+  const warnings: {[K in SystemWarning]: string} = {
+    low_memory: t`Low memory`,
+    "32_bit": t`32-bit mode (${arch})`,
+    software_rendering: t`Software rendering, the GPU driver may be missing`,
+  }
+  const memory = system.memory_mb !== null ?
+    `${system.available_memory_mb ?? "-"} / ${system.memory_mb} MB` : null
+  const items: [string, string | null][] = [
+    [t`os`, system.os],
+    [t`cpu`, system.cpu],
+    [t`memory`, memory],
+    [t`gpu`, system.renderer],
+    [t`gl version`, system.gl_version],
+    [t`display`, system.display],
+    [t`steam branch`, system.steam_branch],
+    [t`language`, system.language],
+    [t`command line`, system.command_line.length > 0 ? system.command_line.join(" ") : null],
+  ]
+
+  return (
+    <>
+      {
+        items.filter(([_, value])=> value).map(([name, value])=> <p key={name}>{name}: {value}</p>)
+      }
+      {
+        system.warnings.map(w=> <p key={w} className="text-yellow-700">{warnings[w]}</p>)
+      }
+    </>
+  )
+}
+
 type ModListProps = {
   comment: LogComment
 }
@@ -134,6 +308,15 @@ function ModList(props: ModListProps) {
   const {comment} = props
   const {t} = useLingui()
   const mods = comment.mods
+  // modinfo.lua of installed mods, read from local disk
+  const [meta, setMeta] = useState<{[moddir: string]: ModMeta}>({})
+  useEffect(()=> {
+    const id = WebviewWindow.getCurrent().label
+    invoke<ModMeta[]>("load_mod_meta", {id}).then(
+      list=> setMeta(Object.fromEntries(list.map(m=> [m.moddir, m]))),
+      console.error,
+    )
+  }, [mods])
 
   return (
     <div className="bg-white/90 border-slate-300 border rounded-sm p-2 my-0.5 text-sm \
@@ -141,6 +324,9 @@ function ModList(props: ModListProps) {
       {
         mods.length === 0 && t`No mod found`
       }
+      {
+        comment.mod_diagnostics.length > 0 && <ModProblems diagnostics={comment.mod_diagnostics}/>
+      }
       {
         mods.map((mod, i)=>
           <div key={mod.moddir} className="">
@@ -159,6 +345,9 @@ function ModList(props: ModListProps) {
                 {mod.workshop_id}
               </span>
             </p>
+            {
+              meta[mod.moddir] && <ModMetaInfo meta={meta[mod.moddir]}/>
+            }
             {
               i !== mods.length - 1 && <div className="h-px bg-slate-300 my-1"></div>
             }
@@ -167,4 +356,194 @@ function ModList(props: ModListProps) {
       }
     </div>
   )
-}
\ No newline at end of file
+}
+
+/** mods sorted by load time, slowest first */
+function ModProfile(props: {profile: ModLoadProfile[]}) {
+  const {t} = useLingui()
+  const total = (p: ModLoadProfile)=> p.load_seconds + p.prefab_seconds
+  const profile = [...props.profile].sort((a, b)=> total(b) - total(a) || a.order - b.order)
+  const max = Math.max(1, ...profile.map(total))
+
+  return (
+    <div className="bg-white/90 border-slate-300 border rounded-sm p-2 my-0.5 text-sm \
+      max-w-full break-words select-text">
+      {
+        profile.length === 0 && t`No mod found`
+      }
+      {
+        profile.map(p=>
+          <div key={p.moddir} className="mb-1"
+            title={t`load order: ${p.order}, prefab files: ${p.prefab_files}, prefabs: ${p.prefabs}`}>
+            <p className="truncate">
+              <span className="opacity-60 mr-1">#{p.order}</span>{p.name}
+            </p>
+            <div className="flex items-center space-x-1">
+              <div className="h-1.5 bg-blue-400 rounded-sm" style={{width: `${total(p) / max * 100}%`}}/>
+              <span className="shrink-0 opacity-70 font-mono">{total(p)}s</span>
+            </div>
+          </div>
+        )
+      }
+    </div>
+  )
+}
+
+function ModProblems(props: {diagnostics: ModDiagnostic[]}) {
+  const {diagnostics} = props
+  const {t} = useLingui()
+  const kindLabel: {[K in ModProblemKind]: string} = {
+    not_loaded: t`Not loaded`,
+    disabled_for_crash: t`Disabled because it crashed`,
+    outdated_api: t`API version mismatch`,
+    missing_dependency: t`Missing dependency`,
+    workshop_failure: t`Workshop download failed`,
+    out_of_date: t`Out of date`,
+    load_error: t`Load error`,
+  }
+
+  return (
+    <div className="mb-2 pb-1 border-b border-slate-300">
+      {
+        diagnostics.map(d=>
+          <div key={d.moddir} className="mb-1">
+            <p className="font-bold text-yellow-700">{d.name || d.moddir}</p>
+            {
+              d.problems.map((p, i)=>
+                <p key={i} className="pl-2" title={p.message}>
+                  {kindLabel[p.kind]}{p.line > 0 && <span className="opacity-60"> ({t`line`} {p.line})</span>}
+                </p>
+              )
+            }
+          </div>
+        )
+      }
+    </div>
+  )
+}
+
+function ModMetaInfo(props: {meta: ModMeta}) {
+  const {meta} = props
+  const {t} = useLingui()
+  const flags = [
+    meta.client_only_mod && t`client only`,
+    meta.server_only_mod && t`server only`,
+    meta.all_clients_require_mod && t`required by all clients`,
+  ].filter(Boolean)
+
+  return (
+    <div className="opacity-70">
+      {
+        meta.author && <p>{t`author`}: {meta.author}</p>
+      }
+      {
+        meta.description && <p className="line-clamp-3 whitespace-pre-line" title={meta.description}>
+          {meta.description}
+        </p>
+      }
+      {
+        flags.length > 0 && <p>{flags.join(", ")}</p>
+      }
+      {
+        meta.api_version !== null && <p>api: {meta.api_version}</p>
+      }
+      {
+        meta.dependencies.length > 0 && <p>{t`dependencies`}: {meta.dependencies.join(", ")}</p>
+      }
+      {
+        meta.options.length > 0 && <p title={meta.options.map(o=> `${o.label || o.name} = ${o.default}`).join("\n")}>
+          {t`config options`}: {meta.options.length}
+        </p>
+      }
+    </div>
+  )
+}
+
+type ClusterInfoProps = {
+  cluster: ClusterConfig
+}
+
+function ClusterInfo(props: ClusterInfoProps) {
+  const {cluster} = props
+  const {t} = useLingui()
+  const settings = cluster.cluster
+  const shard = cluster.shard
+  const level = cluster.level
+  const mods = cluster.mods || []
+  // only show world settings which are changed
+  const overrides = level ? level.overrides.filter(v=> v.value !== "default") : []
+
+  return (
+    <div className="bg-white/90 border-slate-300 border rounded-sm p-2 my-0.5 text-sm \
+      max-w-full break-words select-text">
+      {
+        settings && <>
+          <p className="font-bold">{settings.cluster_name || getName(cluster.cluster_dir)}</p>
+          <p>{t`game mode`}: {settings.game_mode || "-"}</p>
+          <p>{t`max players`}: {settings.max_players ?? "-"}</p>
+          <p>pvp: {settings.pvp === null ? "-" : String(settings.pvp)}</p>
+        </>
+      }
+      {
+        shard && <p>{t`shard`}: {shard.name || "-"}{shard.is_master ? " (master)" : ""}</p>
+      }
+      {
+        level && <p>{t`world`}: {level.name || "-"} ({level.location || "-"})</p>
+      }
+      {
+        overrides.map(v=> <p key={v.name} className="pl-2 font-mono opacity-70">{v.name} = {v.value}</p>)
+      }
+      {
+        cluster.mods && <p>{t`mods enabled`}: {mods.filter(m=> m.enabled).length}</p>
+      }
+      {
+        cluster.errors.map(e=> <p key={e} className="text-red-500">{e}</p>)
+      }
+      {
+        cluster.mod_issues.map(issue=>
+          <p key={issue.kind + issue.moddir} className="text-yellow-700">
+            {
+              issue.kind === "not_loaded" ?
+                t`${issue.moddir} is enabled but not loaded` :
+                t`${issue.name || issue.moddir} is loaded but not enabled in modoverrides.lua`
+            }
+          </p>
+        )
+      }
+    </div>
+  )
+}
+
+type RelatedFilesProps = {
+  files: RelatedFile[]
+}
+
+function RelatedFiles(props: RelatedFilesProps) {
+  const {files} = props
+  const {t} = useLingui()
+  const kindLabel: {[K in RelatedFile["kind"]]: string} = {
+    persist_root: t`Save root`,
+    cluster_dir: t`Cluster`,
+    shard: t`Shard`,
+    session: t`World session`,
+    server_log: t`Server log`,
+  }
+
+  return (
+    <div className="bg-white/90 border-slate-300 border rounded-sm p-2 my-0.5 text-sm \
+      max-w-full break-words">
+      {
+        files.length === 0 && t`No related file found`
+      }
+      {
+        files.map(f=>
+          <p key={f.kind + f.path} title={f.path}
+            className="cursor-pointer hover:text-blue-400 truncate"
+            onClick={()=> invoke("show_file", {path: f.path})}>
+            <span className="opacity-60">{kindLabel[f.kind]}: </span>{f.name}
+          </p>
+        )
+      }
+    </div>
+  )
+}
@@ -1,13 +1,13 @@
 import type { ModInfo } from "./pages/LogBrowserPage"
+import type { LogInfo } from "./bindings/LogInfo"
+import type { SourceSnippet } from "./bindings/SourceSnippet"
 
 declare global {
   interface Window {
-    logPath: {
-      filepath: string,
-      filename: string,
-      is_zip: boolean,
-    },
+    logPath: LogInfo,
     globalModList: ModInfo[],
+    /** code of Lua frames, by `<file>:<line>` as in log */
+    globalLuaSources: {[frame: string]: SourceSnippet},
     currentLocale: string, // zh | en
     currentCopyErrorCommandId: string,
     visitMod: (id: string)=> void,
@@ -5,43 +5,19 @@ import Editor from '@monaco-editor/react'
 import { LANGUAGE_ID, THEME_ID } from './monaco.config'
 import SidePanel from '../../components/SidePanel'
 import { useModKey } from '../../hooks'
+import { formatError } from '../../util'
 
-export type LogInitData = {
-  label: string,
-  exists: boolean,
-  active?: boolean,
-  mtime: number,
-  comment: LogComment,
+import type { LogInitData } from '../../bindings/LogInitData'
+import type { LogRange } from '../../bindings/LogRange'
+import type { ClusterConfig } from '../../bindings/ClusterConfig'
+import type { SourceSnippet } from '../../bindings/SourceSnippet'
+import type { LuaFrame } from '../../bindings/LuaFrame'
 
-  debug_content: string,
-}
-
-export type LogField = {
-  start: number,
-  end: number,
-  type: string,
-  extra?: string,
-}
-
-export type ModInfo = {
-  moddir: string,
-  name: string,
-  version?: string,
-  workshop_id?: string,
-}
-
-export type LogComment = {
-  fields: any[],
-  has_stacktrace: boolean,
-  has_lua_crash: boolean,
-  has_c_crash: boolean,
-  build_version: string,
-  build_platform: string,
-  build_arch: string,
-  databundles_mounting_state: {[K: string]: boolean},
-  total_time: number[],
-  mods: ModInfo[],
-}
+// IPC types are generated from Rust, see `src-tauri/ds-log-core/src/ipc.rs`
+export type { LogInitData } from '../../bindings/LogInitData'
+export type { LogComment } from '../../bindings/LogComment'
+export type { LogField } from '../../bindings/LogField'
+export type { ModInfo } from '../../bindings/ModInfo'
 
 export type NavigateAction = "next-error" | "prev-error" | "next-instance" | "prev-instance"
 
@@ -75,23 +51,48 @@ function jumpTo(editor, neddle: string, dir: "up" | "down") {
   }
 }
 
+/** same text as in log, eg: `../mods/<moddir>/modmain.lua:10` */
+function frameKey(frame: LuaFrame) {
+  const file = frame.moddir ? `../mods/${frame.moddir}/${frame.file}` : frame.file
+  return `${file}:${frame.line}`
+}
+
+/** bytes of each `load_log_range` call */
+const RANGE_SIZE = 1024 * 1024
+
+/** read log text by ranges, the whole content is never sent in one message */
+async function loadContent(id: string, size: number) {
+  const parts: string[] = []
+  let start = 0
+  while (start < size) {
+    const range = await invoke<LogRange>("load_log_range", {id, start, end: start + RANGE_SIZE})
+    if (range.end <= start) break
+    parts.push(range.text)
+    start = range.end
+  }
+  return parts.join("")
+}
+
 export default function LogBrowserPage() {
   /*@ts-ignore*/
   const logPath = window.logPath
   const label = WebviewWindow.getCurrent().label
   const [content, setContent] = useState("")
   const [comment, setComment] = useState<LogComment>(null)
+  const [cluster, setCluster] = useState<ClusterConfig | null>(null)
   const editorRef = useRef(null)
 
   useEffect(() => {
-    invoke<string>("load_log_init", {id: label}).then(
-      res=> {
-        const data = JSON.parse(res) as LogInitData
-        setContent(data.debug_content)
+    invoke<LogInitData>("load_log_init", {id: label}).then(
+      data=> {
         setComment(data.comment)
+        setCluster(data.cluster)
+        // show the reason in editor if the log can not be read
+        if (data.error) return setContent(formatError(data.error))
+        loadContent(label, data.size).then(setContent, err=> console.error(formatError(err)))
       },
       err=> {
-        console.error(err)
+        console.error(formatError(err))
       }
     )
   }
@@ -101,8 +102,16 @@ export default function LogBrowserPage() {
     if (comment) {
       // expose mod list to global
       window.globalModList = comment.mods
+      if (comment.errors.length > 0) {
+        invoke<SourceSnippet[]>("load_lua_sources", {id: label}).then(
+          list=> {
+            window.globalLuaSources = Object.fromEntries(list.map(v=> [frameKey(v.frame), v]))
+          },
+          console.error,
+        )
+      }
     }
-  }, [comment])
+  }, [comment, label])
 
   useEffect(()=> {
     editorRef.current?.setValue(content)
@@ -154,6 +163,7 @@ export default function LogBrowserPage() {
         navigate={navigate}
         logContent={content}
         logComment={comment}
+        cluster={cluster}
       />
       <Editor
         defaultLanguage={LANGUAGE_ID}
@@ -3,6 +3,7 @@ import { getIconPath } from "./geticon"
 import { invoke } from "@tauri-apps/api/core"
 import { i18n } from "@lingui/core"
 import { defineMessage } from "@lingui/core/macro"
+import { formatError } from "../../util"
 
 export const LANGUAGE_ID = "dst_log_file"
 export const THEME_ID = "theme"
@@ -21,6 +22,16 @@ setInterval(()=> {
         window.visitMod(id)
       })
     }
+    // open source of a Lua frame in external editor
+    if (url.getAttribute("data-path")) {
+      let path = decodeURIComponent(url.getAttribute("data-path"))
+      let line = Number(url.getAttribute("data-line"))
+      url.removeAttribute("data-path")
+      url.addEventListener("click", (e)=> {
+        e.preventDefault()
+        invoke("open_in_editor", {path, line}).catch(err=> window.alert(formatError(err)))
+      })
+    }
   }
 }, 50)
 
@@ -41,6 +52,25 @@ loader.init().then(monaco=> {
   monaco.languages.registerHoverProvider(LANGUAGE_ID, {
     provideHover: function (model, position) {
       let lineContent = model.getLineContent(position.lineNumber)
+      // code of a Lua frame, eg: scripts/components/health.lua:123, ../mods/<moddir>/modmain.lua:10
+      for (const match of lineContent.matchAll(/(?<![\w/.-])((?:\.\.\/mods\/[^/"]+\/)?[\w/.-]+\.lua)(?:"\])?(?::|\()(\d+)/g)) {
+        const snippet = window.globalLuaSources?.[`${match[1]}:${match[2]}`]
+        if (snippet && match.index <= position.column && position.column <= match.index + match[0].length + 1) {
+          const code = snippet.lines.map((line, i)=> {
+            const n = snippet.start + i
+            return `${n === snippet.frame.line ? ">" : " "}${String(n).padStart(5)} | ${line}`
+          }).join("\n")
+          return {
+            range: new monaco.Range(position.lineNumber, match.index + 1, position.lineNumber, match.index + match[0].length + 1),
+            contents: [
+              { value: "```lua\n" + code + "\n```" },
+              { value: `*${snippet.origin}*` },
+              snippet.path && { supportHtml: true, value: '<a data-path="' + encodeURIComponent(snippet.path) + '" data-line="'
+                + snippet.frame.line + '" href="#">' + i18n._(defineMessage({ message: "Open in editor" })) + '</a>' },
+            ]
+          }
+        }
+      }
       let regex = /workshop-\d+\b/g
       let matches = [...lineContent.matchAll(regex)]
       for (const match of matches) {
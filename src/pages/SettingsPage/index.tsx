@@ -1,7 +1,10 @@
-import { useCallback, useEffect } from 'react'
+import { useCallback, useEffect, useState } from 'react'
 import { useSetting } from '../../hooks'
 import { emit } from '@tauri-apps/api/event'
-import { useLingui } from '@lingui/react/macro'
+import { invoke } from '@tauri-apps/api/core'
+import { Trans, useLingui } from '@lingui/react/macro'
+import { formatError } from '../../util'
+import type { DiscoveryRoot } from '../../bindings/DiscoveryRoot'
 
 export default function SettingsPage() {
   const { i18n } = useLingui()
@@ -16,6 +19,124 @@ export default function SettingsPage() {
         ]}
         defaultValue={i18n.locale || "en"}
       />
+      <h2 className="mt-4 mb-1"><Trans>Log folders</Trans></h2>
+      <RootList/>
+      <h2 className="mt-4 mb-1"><Trans>External editor</Trans></h2>
+      <ExternalEditor/>
+    </div>
+  )
+}
+
+function RootList() {
+  const [roots, setRoots] = useState<DiscoveryRoot[]>([])
+  const [error, setError] = useState("")
+  const {t} = useLingui()
+
+  useEffect(()=> {
+    invoke<DiscoveryRoot[]>("list_log_roots").then(setRoots, err=> setError(formatError(err)))
+  }, [])
+
+  const update = (promise: Promise<DiscoveryRoot[]>)=> {
+    promise.then(
+      roots=> { setRoots(roots); setError(""); emit("setting", {key: "log_roots"}) },
+      err=> setError(formatError(err)))
+  }
+
+  const kindLabel: {[K in DiscoveryRoot["kind"]]: string} = {
+    documents: t`Documents`,
+    home: "~/.klei",
+    one_drive: "OneDrive",
+    proton: "Proton",
+    dedicated: t`Dedicated server`,
+    user: t`Added`,
+  }
+
+  return (
+    <div className="grid gap-1">
+      {
+        roots.map(({kind, path})=>
+          <div key={path} className="flex items-center p-2 space-x-2 text-sm rounded-sm hover:bg-gray-100">
+            <span className="shrink-0 w-28 text-gray-500">{kindLabel[kind]}</span>
+            <span className="grow break-all select-text">{path}</span>
+            {
+              kind === "user" &&
+              <span className="shrink-0 cursor-pointer px-1 hover:text-red-500"
+                onClick={()=> update(invoke("remove_log_root", {path}))}>
+                ✕
+              </span>
+            }
+          </div>
+        )
+      }
+      {
+        error && <p className="text-sm text-red-500 select-text">{error}</p>
+      }
+      <div>
+        <button className="text-sm px-2 py-1 rounded-sm border cursor-pointer hover:bg-gray-100"
+          onClick={()=> update(invoke("add_log_root"))}>
+          <Trans>Add folder...</Trans>
+        </button>
+      </div>
+    </div>
+  )
+}
+
+const EDITOR_PRESETS = [
+  { label: "VS Code", command: "code -g {file}:{line}" },
+  { label: "Sublime Text", command: "subl {file}:{line}" },
+  { label: "Notepad++", command: "notepad++ -n{line} {file}" },
+]
+
+function ExternalEditor() {
+  const [command, setCommand] = useState("")
+  const [saved, setSaved] = useState("")
+  const [error, setError] = useState("")
+
+  useEffect(()=> {
+    invoke<string | null>("get_external_editor").then(v=> {
+      setCommand(v || "")
+      setSaved(v || "")
+    }, console.error)
+  }, [])
+
+  // a new command is confirmed in a dialog, keep the saved one if it is rejected
+  const save = (value: string)=> {
+    setCommand(value)
+    if (value.trim() === saved) return
+    invoke("set_external_editor", {command: value}).then(
+      ()=> {
+        setSaved(value.trim())
+        setError("")
+      },
+      err=> {
+        setCommand(saved)
+        setError(formatError(err))
+      })
+  }
+
+  return (
+    <div className="grid gap-1 text-sm">
+      <input className="px-2 py-1 rounded-sm border font-mono"
+        value={command}
+        placeholder="code -g {file}:{line}"
+        onChange={e=> setCommand(e.target.value)}
+        onBlur={e=> save(e.target.value)}/>
+      <p className="text-gray-500">
+        <Trans>Opens Lua files of crash frames, {"{file}"} and {"{line}"} are replaced. Leave empty to use the system default app.</Trans>
+      </p>
+      <div className="flex space-x-1">
+        {
+          EDITOR_PRESETS.map(v=>
+            <button key={v.label} className="px-2 py-1 rounded-sm border cursor-pointer hover:bg-gray-100"
+              onClick={()=> save(v.command)}>
+              {v.label}
+            </button>
+          )
+        }
+      </div>
+      {
+        error && <p className="text-red-500 select-text">{error}</p>
+      }
     </div>
   )
 }
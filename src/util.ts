@@ -1,3 +1,10 @@
 export function getName(path: string) {
   return path.split("/").pop().split("\\").pop()
-}
\ No newline at end of file
+}
+
+/** format error from `invoke()`, commands reject with `LogError` */
+export function formatError(err: unknown) {
+  if (err && typeof err === "object" && "message" in err)
+    return String(err.message)
+  return String(err)
+}